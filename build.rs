@@ -0,0 +1,311 @@
+//generates the help pages (text lines and example boards) from "assets/game help.txt"
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const HELP_TEXT_PATH : &str = "assets/game help.txt";
+
+const HELP_PAGE_WIDTH : usize = 30; //characters per line
+const HELP_PAGE_HEIGHT : usize = 17; //lines per page (screen rows 1..=17)
+
+
+struct Board {
+    width : usize,
+    height : usize,
+    field : Vec<i32>,
+    towers : Vec<(usize, usize, i32)>,
+    sinks : Vec<(usize, usize, i32)>, //"@<count>" cells
+    cursor : Option<(usize, usize)>,
+    mode : &'static str,
+}
+
+enum Item {
+    Text(String),
+    Board(Board),
+    InlineBoard(Board), //placed right behind the preceding text line
+}
+
+impl Item {
+    fn lines(&self) -> usize {
+        match self {
+            Item::Board(b) => b.height + if b.cursor.is_some() {2} else {0}, //room for the cursor/arrows
+            Item::InlineBoard(_) => 0,
+            _ => 1,
+        }
+    }
+}
+
+//a paragraph ends with a text line ending a sentence or with a board, headings stay with the paragraph after them
+//blocks are separated by blank lines
+type Paragraph = Vec<Item>;
+type Block = Vec<Paragraph>;
+
+fn count_lines(paragraph : &Paragraph) -> usize {
+    paragraph.iter().map(|i| i.lines()).sum()
+}
+
+struct Page {
+    lines : Vec<String>,
+    boards : Vec<(usize, usize, Board)>, //screen tile x, y
+}
+
+
+fn wrap(text : &str, items : &mut Vec<Item>) {
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        while word.len() > HELP_PAGE_WIDTH {
+            let rest = word.split_off(HELP_PAGE_WIDTH);
+            if !line.is_empty() {
+                items.push(Item::Text(std::mem::take(&mut line)));
+            }
+            items.push(Item::Text(word));
+            word = rest;
+        }
+        if !line.is_empty() && line.len() + 1 + word.len() > HELP_PAGE_WIDTH {
+            items.push(Item::Text(std::mem::take(&mut line)));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        items.push(Item::Text(line));
+    }
+}
+
+fn parse_board(header : &str, rows : &[&str], line_nr : usize) -> Board {
+    let mode = match header.trim_start_matches("[board").trim_end_matches(']').trim() {
+        "" => "None",
+        "move" => "Move",
+        "flatten" => "Flatten",
+        "deflatten" => "Deflatten",
+        m => panic!("{}:{}: unknown board mode '{}'", HELP_TEXT_PATH, line_nr, m),
+    };
+
+    let height = rows.len();
+    let width = rows.first().map_or(0, |r| r.split_whitespace().count());
+    assert!(width>0 && height>0, "{}:{}: empty board", HELP_TEXT_PATH, line_nr);
+
+    let mut field = vec![-1; width*height];
+    let mut tower_ids : Vec<char> = Vec::new();
+    let mut towers : Vec<Option<(usize, usize, i32)>> = Vec::new();
+    let mut sinks = Vec::new();
+    let mut cursor = None;
+
+    for (y, row) in rows.iter().enumerate() {
+        let cells : Vec<&str> = row.split_whitespace().collect();
+        assert!(cells.len()==width, "{}:{}: board rows differ in width", HELP_TEXT_PATH, line_nr + 1 + y);
+        for (x, cell) in cells.iter().enumerate() {
+            let mut cell = *cell;
+            if let Some(c) = cell.strip_suffix('*') {
+                assert!(cursor.is_none(), "{}:{}: more than one cursor", HELP_TEXT_PATH, line_nr + 1 + y);
+                cursor = Some((x, y));
+                cell = c;
+            }
+            if cell=="." {
+                continue;
+            }
+            if let Some(count) = cell.strip_prefix('@') {
+                let count : i32 = count.parse().unwrap_or_else(|_| panic!("{}:{}: bad sink count '{}'", HELP_TEXT_PATH, line_nr + 1 + y, cell));
+                sinks.push((x, y, count));
+                continue;
+            }
+            let id = cell.chars().next().unwrap();
+            assert!(id.is_ascii_lowercase(), "{}:{}: bad board cell '{}'", HELP_TEXT_PATH, line_nr + 1 + y, cell);
+            let tower_index = match tower_ids.iter().position(|t| *t==id) {
+                Some(i) => i,
+                None => {
+                    tower_ids.push(id);
+                    towers.push(None);
+                    tower_ids.len() - 1
+                }
+            };
+            field[x + y*width] = tower_index as i32;
+            if cell.len() > 1 {
+                let height : i32 = cell[1..].parse().unwrap_or_else(|_| panic!("{}:{}: bad tower height '{}'", HELP_TEXT_PATH, line_nr + 1 + y, cell));
+                assert!(towers[tower_index].is_none(), "{}:{}: tower '{}' has two origins", HELP_TEXT_PATH, line_nr + 1 + y, id);
+                towers[tower_index] = Some((x, y, height));
+            }
+        }
+    }
+
+    let towers = towers.into_iter().enumerate()
+        .map(|(i, t)| t.unwrap_or_else(|| panic!("{}:{}: tower '{}' has no origin", HELP_TEXT_PATH, line_nr, tower_ids[i])))
+        .collect();
+
+    if mode!="None" {
+        let (x, y) = cursor.unwrap_or_else(|| panic!("{}:{}: board mode without cursor", HELP_TEXT_PATH, line_nr));
+        if mode!="Move" {
+            assert!(field[x + y*width]!=-1, "{}:{}: selected cell is no tower", HELP_TEXT_PATH, line_nr);
+        }
+    }
+
+    Board { width, height, field, towers, sinks, cursor, mode }
+}
+
+//splits the help text into blocks of paragraphs of wrapped text lines and boards
+fn parse(text : &str) -> Vec<Block> {
+    let mut blocks : Vec<Block> = vec![vec![Vec::new()]];
+    let mut paragraph = String::new();
+    let mut paragraph_done = false; //the next text line starts a new paragraph
+    let lines : Vec<&str> = text.lines().collect();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if line.is_empty() || line.starts_with("[board") {
+            wrap(&paragraph, blocks.last_mut().unwrap().last_mut().unwrap());
+            paragraph.clear();
+        } else if paragraph_done {
+            paragraph_done = false;
+            let block = blocks.last_mut().unwrap();
+            if !block.last().unwrap().is_empty() {
+                block.push(Vec::new());
+            }
+        }
+
+        if line.is_empty() {
+            paragraph_done = false;
+            if blocks.last().unwrap().iter().any(|p| !p.is_empty()) {
+                blocks.push(vec![Vec::new()]);
+            }
+        } else if line.starts_with("[board") {
+            let start = i;
+            i += 1;
+            while i < lines.len() && lines[i].trim()!="[/board]" {
+                i += 1;
+            }
+            assert!(i < lines.len(), "{}:{}: missing [/board]", HELP_TEXT_PATH, start + 1);
+            let board = parse_board(line, &lines[start+1..i], start + 1);
+            let items = blocks.last_mut().unwrap().last_mut().unwrap();
+            //single row boards without cursor fit behind a preceding text line if there is room
+            let inline = board.height==1 && board.cursor.is_none()
+                && matches!(items.last(), Some(Item::Text(t)) if t.len() + 1 + board.width <= HELP_PAGE_WIDTH);
+            items.push(if inline {Item::InlineBoard(board)} else {Item::Board(board)});
+            paragraph_done = true;
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line.trim());
+            //a heading (line ending with ':') always ends its own line, a sentence ending the line ends the paragraph
+            if line.ends_with(':') || line.ends_with('.') {
+                wrap(&paragraph, blocks.last_mut().unwrap().last_mut().unwrap());
+                paragraph.clear();
+                paragraph_done = line.ends_with('.');
+            }
+        }
+        i += 1;
+    }
+    wrap(&paragraph, blocks.last_mut().unwrap().last_mut().unwrap());
+    for block in blocks.iter_mut() {
+        block.retain(|p| !p.is_empty());
+    }
+    blocks.retain(|b| !b.is_empty());
+    blocks
+}
+
+//fills pages block by block, a block only gets split if it does not fit on an empty page
+//and then between its paragraphs, only a paragraph too long for a page gets split between its lines
+fn paginate(blocks : Vec<Block>) -> Vec<Page> {
+    let mut pages = vec![Page { lines : Vec::new(), boards : Vec::new() }];
+
+    for block in blocks {
+        let block_lines : usize = block.iter().map(count_lines).sum();
+        let page = pages.last().unwrap();
+        let needed = block_lines + if page.lines.is_empty() {0} else {1};
+        if !page.lines.is_empty() && page.lines.len() + needed > HELP_PAGE_HEIGHT && block_lines <= HELP_PAGE_HEIGHT {
+            pages.push(Page { lines : Vec::new(), boards : Vec::new() });
+        }
+        //empty line between blocks, unless the block starts a page
+        let mut separate = !pages.last().unwrap().lines.is_empty();
+
+        for paragraph in block {
+            let paragraph_lines = count_lines(&paragraph);
+            let page = pages.last().unwrap();
+            let needed = paragraph_lines + if separate {1} else {0};
+            if !page.lines.is_empty() && page.lines.len() + needed > HELP_PAGE_HEIGHT && paragraph_lines <= HELP_PAGE_HEIGHT {
+                pages.push(Page { lines : Vec::new(), boards : Vec::new() });
+                separate = false;
+            }
+            if separate {
+                pages.last_mut().unwrap().lines.push(String::new());
+                separate = false;
+            }
+
+            for item in paragraph {
+                let item_lines = item.lines();
+                assert!(item_lines <= HELP_PAGE_HEIGHT, "board too high for a help page");
+                if pages.last().unwrap().lines.len() + item_lines > HELP_PAGE_HEIGHT {
+                    pages.push(Page { lines : Vec::new(), boards : Vec::new() });
+                }
+                let page = pages.last_mut().unwrap();
+                match item {
+                    Item::Text(t) => page.lines.push(t),
+                    Item::InlineBoard(b) => {
+                        let y = page.lines.len() - 1;
+                        let x = page.lines[y].len() + 1;
+                        page.boards.push((x, y, b));
+                    },
+                    Item::Board(b) => {
+                        let y = page.lines.len() + if b.cursor.is_some() {1} else {0};
+                        let x = (HELP_PAGE_WIDTH - b.width)/2;
+                        assert!(b.cursor.is_none() || page.boards.iter().all(|(_, _, pb)| pb.cursor.is_none()), "only one board with cursor per help page supported");
+                        for _ in 0..item_lines {
+                            page.lines.push(String::new());
+                        }
+                        page.boards.push((x, y, b));
+                    }
+                }
+            }
+        }
+    }
+
+    pages
+}
+
+fn generate(pages : &[Page]) -> String {
+    let mut out = String::new();
+    writeln!(out, "//generated by build.rs from \"{}\"", HELP_TEXT_PATH).unwrap();
+    writeln!(out, "const HELP_PAGES : [HelpPage; {}] = [", pages.len()).unwrap();
+    for page in pages {
+        writeln!(out, "    HelpPage {{").unwrap();
+        writeln!(out, "        text : &[").unwrap();
+        for line in page.lines.iter() {
+            writeln!(out, "            {:?},", line).unwrap();
+        }
+        writeln!(out, "        ],").unwrap();
+        writeln!(out, "        boards : &[").unwrap();
+        for (x, y, b) in page.boards.iter() {
+            writeln!(out, "            HelpBoard {{").unwrap();
+            writeln!(out, "                pos : ({}, {}),", x, y + 1).unwrap();
+            writeln!(out, "                width : {},", b.width).unwrap();
+            writeln!(out, "                height : {},", b.height).unwrap();
+            writeln!(out, "                field : &{:?},", b.field).unwrap();
+            writeln!(out, "                towers : &{:?},", b.towers).unwrap();
+            writeln!(out, "                sinks : &{:?},", b.sinks).unwrap();
+            writeln!(out, "                cursor : {:?},", b.cursor).unwrap();
+            writeln!(out, "                mode : HelpBoardMode::{},", b.mode).unwrap();
+            writeln!(out, "            }},").unwrap();
+        }
+        writeln!(out, "        ],").unwrap();
+        writeln!(out, "    }},").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    out
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", HELP_TEXT_PATH);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let text = fs::read_to_string(HELP_TEXT_PATH).expect("help text not readable");
+    let pages = paginate(parse(&text));
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("help_pages.rs"), generate(&pages)).unwrap();
+}
\ No newline at end of file
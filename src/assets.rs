@@ -3,13 +3,13 @@ use agb::{display::object::{Graphics, TagMap}, include_aseprite, include_backgro
 
 
 
-include_background_gfx!(priv_menu_bg_gfx, bg => "assets/tex/bg.png", menu => "assets/tex/menu.png", help1 => "assets/tex/help_splitted1.png", help2 => "assets/tex/help_splitted2.png");
-pub mod menu_bg_gfx {
-    pub use super::priv_menu_bg_gfx::*;
+include_background_gfx!(priv_game_bg_gfx, bg => "assets/tex/bg.png", tiles => "assets/tex/tiles.png", nums => "assets/tex/nums.png", menu => "assets/tex/menu.png", font => "assets/tex/font.png");
+pub mod game_bg_gfx {
+    pub use super::priv_game_bg_gfx::*;
 }
 
-include_background_gfx!(priv_game_bg_gfx, bg => "assets/tex/bg.png", tiles => "assets/tex/tiles.png", nums => "assets/tex/nums.png", menu => "assets/tex/menu.png");
-pub mod game_bg_gfx {
+//the menu draws the example boards of the help pages with the game tiles, so both share the same graphics
+pub mod menu_bg_gfx {
     pub use super::priv_game_bg_gfx::*;
 }
 
@@ -25,5 +25,12 @@ pub static TAG_MAP: &TagMap = GRAPHICS.tags();
 
 pub static CURSOR_MOVE_SOUND: &[u8] = include_wav!("assets/snd/hover.wav");
 pub static SELECT_SOUND: &[u8] = include_wav!("assets/snd/select.wav");
-pub static FLATTEN_DEFLATTEN_SOUND: &[u8] = include_wav!("assets/snd/flatten_anim.wav");
-pub static SOLVED_SOUND: &[u8] = include_wav!("assets/snd/solved.wav");
\ No newline at end of file
+pub static CLICK_SOUND: &[u8] = include_wav!("assets/snd/click.wav");
+pub static FLATTEN_DEFLATTEN_SOUND: &[u8] = include_wav!("assets/snd/flatten_anim.wav"); //mono
+pub static FLATTEN_SOUND: &[u8] = include_wav!("assets/snd/flatten.wav"); //mono
+pub static DEFLATTEN_SOUND: &[u8] = include_wav!("assets/snd/deflatten.wav"); //mono
+pub static FLATTEN_PREVIEW_SOUND: &[u8] = include_wav!("assets/snd/flatten_preview.wav");
+pub static SOLVED_SOUND: &[u8] = include_wav!("assets/snd/solved.wav");
+
+pub static MENU_MUSIC: &[u8] = include_wav!("assets/snd/music_menu.wav"); //mono
+pub static INGAME_MUSIC: &[u8] = include_wav!("assets/snd/music_ingame.wav"); //mono
\ No newline at end of file
@@ -0,0 +1,117 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use agb::sound::mixer::{ChannelId, Mixer, SoundChannel};
+
+use crate::game::Settings;
+
+use super::*;
+
+
+//max simultaneous voices and min frames between two triggers of an event (in SoundEvent order)
+//auto repeated cursor moves would otherwise start a new channel every few frames
+const EVENT_LIMITS : [(usize, u32); SOUND_EVENTS_COUNT] = [
+    (1, 8), //CursorMove, every second auto repeat
+    (1, 4), //Select
+    (2, 2), //Flatten
+    (2, 2), //Deflatten
+    (1, 8), //Blocked
+    (1, 2), //TowerFlattened
+    (1, 30), //Solved
+    (1, 4), //MenuBack
+    (1, 4), //DragPreview
+];
+
+
+//owns the mixer, every sound effect and the music get played through here
+pub struct AudioService<'gba> {
+    gba_mixer : Mixer<'gba>,
+    music_player : MusicPlayer,
+
+    effects_volume : u8,
+    sound_theme : SoundTheme,
+
+    frame_nr : u32,
+    voices : [Vec<ChannelId>; SOUND_EVENTS_COUNT], //oldest first
+    last_played : [Option<u32>; SOUND_EVENTS_COUNT], //frame_nr of the last trigger
+}
+
+impl<'gba> AudioService<'gba> {
+
+    pub fn new(mut gba_mixer : Mixer<'gba>, game_settings : &Settings) -> Self {
+        gba_mixer.enable();
+        AudioService {
+            gba_mixer,
+            music_player : MusicPlayer::new(),
+
+            effects_volume : game_settings.effects_volume,
+            sound_theme : game_settings.sound_theme,
+
+            frame_nr : 0,
+            voices : Default::default(),
+            last_played : [None; SOUND_EVENTS_COUNT],
+        }
+    }
+
+    //effects played afterwards use the new volume and theme, the music follows with the next frame
+    pub fn apply_settings(&mut self, game_settings : &Settings) {
+        self.effects_volume = game_settings.effects_volume;
+        self.sound_theme = game_settings.sound_theme;
+    }
+
+    //must be called once per frame
+    pub fn frame(&mut self, game_settings : &Settings) {
+        self.gba_mixer.frame();
+        self.music_player.update(&mut self.gba_mixer, game_settings);
+        self.apply_settings(game_settings);
+        self.frame_nr = self.frame_nr.wrapping_add(1);
+    }
+
+    pub fn play_music(&mut self, track : MusicTrack) {
+        self.music_player.play(track);
+    }
+
+    pub fn play(&mut self, event : SoundEvent) {
+        if self.effects_volume==0 {
+            return;
+        }
+
+        let idx = event.index();
+        let (max_voices, min_frames) = EVENT_LIMITS[idx];
+
+        //coalesce repeated triggers
+        if let Some(last) = self.last_played[idx] {
+            if self.frame_nr.wrapping_sub(last)<min_frames {
+                return;
+            }
+        }
+
+        let Some(sample) = self.sound_theme.sample(event) else {
+            return;
+        };
+
+        //forget finished voices, then make room by stopping the oldest ones
+        let gba_mixer = &mut self.gba_mixer;
+        let voices = &mut self.voices[idx];
+        voices.retain(|id| gba_mixer.channel(id).is_some());
+        while voices.len()>=max_voices {
+            let oldest = voices.remove(0);
+            if let Some(channel) = gba_mixer.channel(&oldest) {
+                channel.stop();
+            }
+        }
+
+        let mut sc = SoundChannel::new(sample.data);
+        sc.volume(volume_from_setting(self.effects_volume, 1, 1));
+        if sample.stereo {
+            sc.stereo();
+        } else {
+            sc.playback(event.playback_speed());
+        }
+        if let Some(id) = gba_mixer.play_sound(sc) {
+            voices.push(id);
+        }
+        self.last_played[idx] = Some(self.frame_nr);
+    }
+}
\ No newline at end of file
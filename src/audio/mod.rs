@@ -0,0 +1,7 @@
+mod audio_service;
+mod music;
+mod sound_events;
+
+pub use audio_service::*;
+pub use music::*;
+pub use sound_events::*;
\ No newline at end of file
@@ -0,0 +1,100 @@
+use agb::fixnum::Num;
+use agb::sound::mixer::{ChannelId, Mixer, SoundChannel};
+
+use crate::assets::*;
+use crate::game::Settings;
+
+
+pub const MAX_VOLUME : u8 = 10; //volume settings go from 0 (off) to MAX_VOLUME in steps of 1
+
+const CROSSFADE_FRAMES : i32 = 90;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MusicTrack {
+    Menu,
+    Ingame,
+}
+
+impl MusicTrack {
+    fn data(&self) -> &'static [u8] {
+        match self {
+            MusicTrack::Menu => MENU_MUSIC,
+            MusicTrack::Ingame => INGAME_MUSIC,
+        }
+    }
+}
+
+//volume setting scaled by fade/fade_max as mixer volume
+pub fn volume_from_setting(setting : u8, fade : i32, fade_max : i32) -> Num<i16, 8> {
+    Num::from_raw((256*setting.min(MAX_VOLUME) as i32*fade/(MAX_VOLUME as i32*fade_max)) as i16)
+}
+
+struct MusicChannel {
+    id : ChannelId,
+    track : MusicTrack,
+    fade : i32, //0..=CROSSFADE_FRAMES
+}
+
+//plays looping music tracks, switching tracks crossfades the old into the new one
+pub struct MusicPlayer {
+    current : Option<MusicChannel>,
+    fading_out : Option<MusicChannel>,
+    requested : Option<MusicTrack>,
+}
+
+impl MusicPlayer {
+
+    pub fn new() -> Self {
+        MusicPlayer {
+            current : None,
+            fading_out : None,
+            requested : None,
+        }
+    }
+
+    //the switch happens with the next update
+    pub fn play(&mut self, track : MusicTrack) {
+        self.requested = Some(track);
+    }
+
+    //must be called once per frame
+    pub fn update(&mut self, gba_mixer : &mut Mixer, game_settings : &Settings) {
+
+        if let Some(track) = self.requested.take() {
+            if self.current.as_ref().map(|c| c.track)!=Some(track) {
+                if let Some(old) = self.fading_out.take() {
+                    if let Some(channel) = gba_mixer.channel(&old.id) {
+                        channel.stop();
+                    }
+                }
+                self.fading_out = self.current.take();
+
+                let mut sc = SoundChannel::new_high_priority(track.data());
+                sc.should_loop().volume(0);
+                self.current = gba_mixer.play_sound(sc).map(|id| MusicChannel { id, track, fade : 0 });
+            }
+        }
+
+        if let Some(current) = &mut self.current {
+            current.fade = (current.fade+1).min(CROSSFADE_FRAMES);
+            if let Some(channel) = gba_mixer.channel(&current.id) {
+                channel.volume(volume_from_setting(game_settings.music_volume, current.fade, CROSSFADE_FRAMES));
+            }
+        }
+
+        if let Some(old) = &mut self.fading_out {
+            old.fade-=1;
+            if let Some(channel) = gba_mixer.channel(&old.id) {
+                if old.fade<=0 {
+                    channel.stop();
+                } else {
+                    channel.volume(volume_from_setting(game_settings.music_volume, old.fade, CROSSFADE_FRAMES));
+                }
+            }
+            if old.fade<=0 {
+                self.fading_out = None;
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+use agb::fixnum::Num;
+
+use crate::assets::*;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SoundEvent {
+    CursorMove,
+    Select,
+    Flatten { height : i32 }, //remaining (flattened) height of the tower after the move
+    Deflatten { height : i32 },
+    Blocked,
+    TowerFlattened, //tower reached height 1
+    Solved,
+    MenuBack,
+    DragPreview, //the previewed tiles of a drag changed
+}
+
+pub const SOUND_EVENTS_COUNT : usize = 9;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SoundTheme {
+    Classic, //the sounds of the jam version, flatten and deflatten share one sample
+    Extended,
+}
+
+pub const SOUND_THEMES_COUNT : usize = 2;
+
+pub struct SoundSample {
+    pub data : &'static [u8],
+    pub stereo : bool, //only mono samples can be pitched
+}
+
+static HOVER : SoundSample = SoundSample { data : CURSOR_MOVE_SOUND, stereo : true };
+static SELECT : SoundSample = SoundSample { data : SELECT_SOUND, stereo : true };
+static CLICK : SoundSample = SoundSample { data : CLICK_SOUND, stereo : true };
+static FLATTEN_ANIM : SoundSample = SoundSample { data : FLATTEN_DEFLATTEN_SOUND, stereo : false };
+static FLATTEN : SoundSample = SoundSample { data : FLATTEN_SOUND, stereo : false };
+static DEFLATTEN : SoundSample = SoundSample { data : DEFLATTEN_SOUND, stereo : false };
+static FLATTEN_PREVIEW : SoundSample = SoundSample { data : FLATTEN_PREVIEW_SOUND, stereo : true };
+static SOLVED : SoundSample = SoundSample { data : SOLVED_SOUND, stereo : true };
+
+//sample per event (in SoundEvent order), None => event stays silent
+static CLASSIC_THEME : [Option<&SoundSample>; SOUND_EVENTS_COUNT] = [
+    Some(&HOVER),
+    Some(&SELECT),
+    Some(&FLATTEN_ANIM),
+    Some(&FLATTEN_ANIM),
+    None,
+    Some(&FLATTEN_ANIM),
+    Some(&SOLVED),
+    Some(&SELECT),
+    Some(&HOVER),
+];
+
+static EXTENDED_THEME : [Option<&SoundSample>; SOUND_EVENTS_COUNT] = [
+    Some(&HOVER),
+    Some(&SELECT),
+    Some(&FLATTEN),
+    Some(&DEFLATTEN),
+    Some(&CLICK),
+    Some(&FLATTEN_PREVIEW),
+    Some(&SOLVED),
+    Some(&CLICK),
+    Some(&FLATTEN_PREVIEW),
+];
+
+const PITCH_STEPS : i32 = 8; //towers of height 1 play PITCH_STEPS/16 faster than towers of height > PITCH_STEPS
+
+
+impl SoundEvent {
+
+    pub fn index(&self) -> usize {
+        match self {
+            SoundEvent::CursorMove => 0,
+            SoundEvent::Select => 1,
+            SoundEvent::Flatten { .. } => 2,
+            SoundEvent::Deflatten { .. } => 3,
+            SoundEvent::Blocked => 4,
+            SoundEvent::TowerFlattened => 5,
+            SoundEvent::Solved => 6,
+            SoundEvent::MenuBack => 7,
+            SoundEvent::DragPreview => 8,
+        }
+    }
+
+    //the lower the remaining tower, the higher the pitch
+    pub fn playback_speed(&self) -> Num<u32, 8> {
+        match self {
+            SoundEvent::Flatten { height } | SoundEvent::Deflatten { height } => {
+                let steps = (PITCH_STEPS - (height - 1).clamp(0, PITCH_STEPS)) as u32;
+                Num::new(1) + Num::from_raw(steps*16)
+            },
+            _ => Num::new(1),
+        }
+    }
+}
+
+impl SoundTheme {
+
+    pub fn from_u8(v : u8) -> SoundTheme {
+        match v {
+            1 => SoundTheme::Extended,
+            _ => SoundTheme::Classic,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            SoundTheme::Classic => 0,
+            SoundTheme::Extended => 1,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SoundTheme::Classic => "CLASSIC",
+            SoundTheme::Extended => "EXTENDED",
+        }
+    }
+
+    pub fn sample(&self, event : SoundEvent) -> Option<&'static SoundSample> {
+        match self {
+            SoundTheme::Classic => CLASSIC_THEME[event.index()],
+            SoundTheme::Extended => EXTENDED_THEME[event.index()],
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,124 @@
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use agb::input::Button;
+
+use agb::display::object::OamManaged;
+use agb::display::tiled::{Tiled0, VRamManager};
+
+use crate::audio::AudioService;
+use crate::game;
+use crate::game::logic::{AiPlayer, PlayingField, SpreadRule, AI_SKILLS_COUNT};
+use crate::game::view::PlayingFieldView;
+use crate::game::{step_seed, ReplayMove};
+use crate::util::gbaex::ButtonControllerAutoRepeat;
+
+
+const DEMO_BOARD_WIDTH : usize = 8;
+const DEMO_BOARD_HEIGHT : usize = 6;
+const DEMO_BOARD_TRIES : usize = 8; //boards generated until the ai player manages one
+const DEMO_TEST_MAX_MOVES : u32 = 2000;
+const AI_MOVE_FRAMES : u32 = 30; //between two moves of the ai player
+const DEMO_SOLVED_FRAMES : u32 = 180; //the finished board stays visible before the next one starts
+
+
+//the ai player spreads boards on the real renderer, either small random boards one after another
+//while the menu is left alone, or the saved board of the player
+pub struct DemoView<'gba> {
+    pf : PlayingField,
+    pfv : PlayingFieldView<'gba>,
+
+    ai_player : AiPlayer,
+    skill : usize,
+    seed : Option<[u32; 4]>, //None => the saved board is watched, no new boards
+    frame : u32, //since the last move
+
+    finished : bool,
+}
+
+impl<'gba> DemoView<'gba> {
+
+    //random boards with the best ai player
+    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, game_settings : &game::Settings) -> Self {
+        let mut dv = Self::new_empty(gba_tiled0, gba_vram, gba_oam, game_settings, AI_SKILLS_COUNT - 1);
+        dv.seed = Some(game_settings.playing_field_seed);
+        dv.pfv.set_status_text(Some(String::from("DEMO")));
+        dv.start_board();
+        dv
+    }
+
+    //the ai player of the chosen skill goes on with the saved board
+    pub fn new_watch(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, game_settings : &game::Settings, playing_field_data : &Vec<u8>) -> Self {
+        let mut dv = Self::new_empty(gba_tiled0, gba_vram, gba_oam, game_settings, game_settings.ai_skill as usize);
+        dv.pfv.load_from_u8_vec(&mut dv.pf, playing_field_data);
+        dv.pfv.set_status_text(Some(String::from("AI PLAYING")));
+        dv
+    }
+
+    fn new_empty(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, game_settings : &game::Settings, skill : usize) -> Self {
+        DemoView {
+            pf : PlayingField::new(game_settings.playing_field_width, game_settings.playing_field_height, None),
+            pfv : PlayingFieldView::new(gba_tiled0, gba_vram, gba_oam, game_settings.tower_palette),
+
+            ai_player : AiPlayer::new(skill),
+            skill,
+            seed : None,
+            frame : 0,
+
+            finished : false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    //auto tested, boards the ai player fails on are skipped (as long as there are tries left)
+    fn start_board(&mut self) {
+        let Some(seed) = &mut self.seed else {
+            return;
+        };
+        for _ in 0..DEMO_BOARD_TRIES {
+            step_seed(seed);
+            self.pf = PlayingField::new(DEMO_BOARD_WIDTH, DEMO_BOARD_HEIGHT, None);
+            self.pf.spread_rule = SpreadRule::Straight;
+            self.pfv.init_with_random_towers(&mut self.pf, Some(*seed), 0, 0, false, false);
+            if self.pf.is_solvable_by_ai(self.skill, DEMO_TEST_MAX_MOVES) {
+                break;
+            }
+        }
+        self.ai_player = AiPlayer::new(self.skill);
+        self.frame = 0;
+    }
+
+    //any button goes back to the menu
+    pub fn handle_input(&mut self, gba_input : &ButtonControllerAutoRepeat, audio : &mut AudioService) {
+
+        if gba_input.btn_ctrl.is_just_pressed(Button::all()) {
+            self.finished = true;
+            return;
+        }
+
+        self.frame += 1;
+        if self.frame<AI_MOVE_FRAMES {
+            return;
+        }
+
+        if let Some(mv) = self.ai_player.calc_next_move(&self.pf) {
+            self.frame = 0;
+            self.pfv.replay_move(&mut self.pf, &ReplayMove { frame : 0, tower_xy : mv.tower_xy, xys : mv.xys, flatten : mv.flatten }, audio);
+        } else if self.seed.is_some() {
+            if self.frame>=DEMO_SOLVED_FRAMES {
+                self.start_board();
+            }
+        } else {
+            self.pfv.set_status_text(Some(String::from(if self.ai_player.has_given_up() {"AI GAVE UP"} else {"AI SOLVED"})));
+        }
+    }
+
+    pub fn update(&mut self, gba_vram : &mut VRamManager, gba_oam : &OamManaged) {
+        self.pfv.update(&self.pf, gba_vram, gba_oam);
+    }
+}
\ No newline at end of file
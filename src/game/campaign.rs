@@ -0,0 +1,124 @@
+use super::logic::{lin2d, PlayingField, MAX_PLAYING_FIELD_HEIGHT, MAX_PLAYING_FIELD_WIDTH, NO_TOWER};
+use super::{step_seed, DEFAULT_SEED};
+
+
+pub const CAMPAIGN_CHAPTERS_COUNT : usize = 10;
+pub const CAMPAIGN_LEVELS_PER_CHAPTER : usize = 10;
+pub const CAMPAIGN_LEVELS_COUNT : usize = CAMPAIGN_CHAPTERS_COUNT*CAMPAIGN_LEVELS_PER_CHAPTER;
+pub const CAMPAIGN_MAX_STARS : u8 = 3; //one for solving, one for the time, one for the moves
+pub const CAMPAIGN_DATA_LEN : usize = 1 + CAMPAIGN_LEVELS_COUNT; //bytes in the save data
+
+const CAMPAIGN_MIN_SIZE : usize = 5;
+const PAR_FRAMES_PER_TILE : u32 = 2*60;
+
+
+//board of a campaign level, the same on every cartridge
+pub struct CampaignLevel {
+    pub width : usize,
+    pub height : usize,
+    pub rocks_level : u8, //0..OBSTACLE_LEVELS_COUNT
+    pub locked_level : u8, //0..OBSTACLE_LEVELS_COUNT
+    pub seed : [u32; 4],
+}
+
+impl CampaignLevel {
+
+    //sizes grow from 5x5 to the biggest board, the early chapters come with pre-filled tiles, the later ones with rocks
+    pub fn new(level : usize) -> Self {
+        let last = CAMPAIGN_LEVELS_COUNT - 1;
+        let chapter = level/CAMPAIGN_LEVELS_PER_CHAPTER;
+
+        let mut seed = DEFAULT_SEED;
+        seed[0] ^= (level as u32 + 1).wrapping_mul(0x9e3779b9);
+        seed[2] ^= level as u32;
+        for _ in 0..16 {
+            step_seed(&mut seed);
+        }
+
+        CampaignLevel {
+            width : CAMPAIGN_MIN_SIZE + (MAX_PLAYING_FIELD_WIDTH - CAMPAIGN_MIN_SIZE)*level/last,
+            height : CAMPAIGN_MIN_SIZE + (MAX_PLAYING_FIELD_HEIGHT - CAMPAIGN_MIN_SIZE)*level/last,
+            rocks_level : if chapter>=CAMPAIGN_CHAPTERS_COUNT/2 {1} else {0},
+            locked_level : 2u8.saturating_sub(chapter as u8/3),
+            seed,
+        }
+    }
+
+    //2 seconds per tile
+    pub fn par_frames(&self) -> u32 {
+        (self.width*self.height) as u32*PAR_FRAMES_PER_TILE
+    }
+
+    //tiles the towers of the solution spread to, pre-filled ones excluded, with some slack
+    //the moves of the player are counted in tiles as well
+    pub fn par_moves(pf : &PlayingField) -> u32 {
+        let mut tiles = 0;
+        for xy in 0..pf.area {
+            let tower_idx = pf.field_solution[xy];
+            if tower_idx==NO_TOWER || pf.is_locked(((xy%pf.width) as i32, (xy/pf.width) as i32)) {
+                continue;
+            }
+            let tower = &pf.towers[tower_idx as usize];
+            if xy!=lin2d!(tower.x, tower.y, pf.width) {
+                tiles += 1;
+            }
+        }
+        tiles + tiles/4
+    }
+
+    pub fn calc_stars(&self, pf : &PlayingField, frames : u32, moves : u32) -> u8 {
+        1 + (if frames<=self.par_frames() {1} else {0}) + (if moves<=Self::par_moves(pf) {1} else {0})
+    }
+}
+
+
+//stars reached per level and the level selected in the menu
+pub struct Campaign {
+    pub level : u8, //0..CAMPAIGN_LEVELS_COUNT
+    stars : [u8; CAMPAIGN_LEVELS_COUNT], //0 => not solved yet
+}
+
+impl Campaign {
+
+    pub fn new() -> Self {
+        Campaign {
+            level : 0,
+            stars : [0; CAMPAIGN_LEVELS_COUNT],
+        }
+    }
+
+    pub fn stars(&self, level : usize) -> u8 {
+        self.stars[level]
+    }
+
+    //a chapter opens up once every level of the previous one is solved
+    pub fn is_chapter_unlocked(&self, chapter : usize) -> bool {
+        chapter==0 || self.stars[(chapter-1)*CAMPAIGN_LEVELS_PER_CHAPTER..chapter*CAMPAIGN_LEVELS_PER_CHAPTER].iter().all(|s| *s>0)
+    }
+
+    pub fn is_level_unlocked(&self, level : usize) -> bool {
+        self.is_chapter_unlocked(level/CAMPAIGN_LEVELS_PER_CHAPTER)
+    }
+
+    //keeps the best rating, the menu moves on to the next level
+    pub fn complete_level(&mut self, level : usize, stars : u8) {
+        self.stars[level] = self.stars[level].max(stars);
+        if level + 1<CAMPAIGN_LEVELS_COUNT && self.level as usize==level {
+            self.level += 1;
+        }
+    }
+
+    pub fn save_as_u8_arr(&self) -> [u8; CAMPAIGN_DATA_LEN] {
+        let mut data = [0; CAMPAIGN_DATA_LEN];
+        data[0] = self.level;
+        data[1..].copy_from_slice(&self.stars);
+        data
+    }
+
+    pub fn load_from_u8_slice(&mut self, data : &[u8]) {
+        self.level = data[0].min(CAMPAIGN_LEVELS_COUNT as u8 - 1);
+        for (s, d) in self.stars.iter_mut().zip(data[1..].iter()) {
+            *s = (*d).min(CAMPAIGN_MAX_STARS);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,119 @@
+use agb::input::Button;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Action {
+    Flatten,
+    Deflatten,
+    Undo,
+    Hint, //held together with a direction the cursor jumps instead
+    Pause,
+    NextTower,
+}
+
+pub const ACTIONS_COUNT : usize = 6;
+
+//buttons an action can be mapped to, the d-pad always moves the cursor
+pub const MAPPABLE_BUTTONS : [Button; 6] = [Button::A, Button::B, Button::L, Button::R, Button::START, Button::SELECT];
+pub const MAPPABLE_BUTTON_NAMES : [&str; 6] = ["A", "B", "L", "R", "START", "SELECT"];
+
+//index into MAPPABLE_BUTTONS per action (in Action order)
+const DEFAULT_ACTION_BUTTONS : [u8; ACTIONS_COUNT] = [0, 1, 2, 3, 4, 5];
+
+pub const MIN_REPEAT_DELAY : u8 = 6;
+pub const MAX_REPEAT_DELAY : u8 = 40;
+pub const MAX_REPEAT_SPEED : u8 = 8; //speed 1..=MAX_REPEAT_SPEED, repeats every MAX_REPEAT_SPEED+1-speed frames
+
+pub const CONTROLS_DATA_LEN : usize = 2 + ACTIONS_COUNT; //bytes in the save data
+
+
+impl Action {
+
+    pub fn index(&self) -> usize {
+        match self {
+            Action::Flatten => 0,
+            Action::Deflatten => 1,
+            Action::Undo => 2,
+            Action::Hint => 3,
+            Action::Pause => 4,
+            Action::NextTower => 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Controls {
+    pub repeat_delay : u8, //frames a button must be held before auto repeating starts
+    pub repeat_speed : u8,
+    action_buttons : [u8; ACTIONS_COUNT], //every button is used by at most one action
+}
+
+impl Controls {
+
+    pub fn new() -> Self {
+        Controls {
+            repeat_delay : 20,
+            repeat_speed : 5,
+            action_buttons : DEFAULT_ACTION_BUTTONS,
+        }
+    }
+
+    //same repeat settings, but the buttons the tutorial texts talk about
+    pub fn with_default_buttons(&self) -> Self {
+        Controls {
+            action_buttons : DEFAULT_ACTION_BUTTONS,
+            ..*self
+        }
+    }
+
+    //frames between two auto repeats
+    pub fn repeat_interval(&self) -> u8 {
+        MAX_REPEAT_SPEED + 1 - self.repeat_speed.clamp(1, MAX_REPEAT_SPEED)
+    }
+
+    pub fn button_index(&self, action : Action) -> usize {
+        self.action_buttons[action.index()] as usize
+    }
+
+    pub fn button(&self, action : Action) -> Button {
+        MAPPABLE_BUTTONS[self.button_index(action)]
+    }
+
+    pub fn button_name(&self, action : Action) -> &'static str {
+        MAPPABLE_BUTTON_NAMES[self.button_index(action)]
+    }
+
+    //an action which already used the button gets the old button of action
+    pub fn assign(&mut self, action : Action, button_index : usize) {
+        let old = self.action_buttons[action.index()];
+        for b in self.action_buttons.iter_mut() {
+            if *b as usize==button_index {
+                *b = old;
+            }
+        }
+        self.action_buttons[action.index()] = button_index as u8;
+    }
+
+    pub fn save_as_u8_arr(&self) -> [u8; CONTROLS_DATA_LEN] {
+        let mut data = [0; CONTROLS_DATA_LEN];
+        data[0] = self.repeat_delay;
+        data[1] = self.repeat_speed;
+        data[2..].copy_from_slice(&self.action_buttons);
+        data
+    }
+
+    //invalid mappings (e.g. two actions on one button) fall back to the default buttons
+    pub fn load_from_u8_slice(&mut self, data : &[u8]) {
+        self.repeat_delay = data[0].clamp(MIN_REPEAT_DELAY, MAX_REPEAT_DELAY);
+        self.repeat_speed = data[1].clamp(1, MAX_REPEAT_SPEED);
+
+        let buttons = &data[2..CONTROLS_DATA_LEN];
+        let valid = buttons.iter().enumerate().all(|(i, b)| {
+            (*b as usize)<MAPPABLE_BUTTONS.len() && !buttons[..i].contains(b)
+        });
+        self.action_buttons = DEFAULT_ACTION_BUTTONS;
+        if valid {
+            self.action_buttons.copy_from_slice(buttons);
+        }
+    }
+}
\ No newline at end of file
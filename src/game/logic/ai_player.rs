@@ -0,0 +1,206 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::forced_moves::ForcedMove;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+pub const AI_SKILLS_COUNT : usize = 3;
+const AI_SKILL_MAX_GUESSES : [usize; AI_SKILLS_COUNT] = [1, 4, 16]; //open guesses the player keeps in mind at once
+
+
+//flatten or deflatten as a player would do it
+pub struct AiMove {
+    pub tower_xy : (i32, i32),
+    pub xys : Vec<(i32, i32)>,
+    pub flatten : bool,
+}
+
+impl AiMove {
+
+    fn new(spread : &ForcedMove, flatten : bool) -> Self {
+        AiMove { tower_xy : spread.tower_xy, xys : spread.xys.clone(), flatten }
+    }
+
+    pub fn apply(&self, pf : &mut PlayingField) {
+        if self.flatten {
+            pf.flatten(self.tower_xy, self.xys.clone());
+        } else {
+            pf.deflatten(self.tower_xy, self.xys.clone());
+        }
+    }
+}
+
+struct AiLevel {
+    spreads : Vec<ForcedMove>, //alternatives for the same tower, more than one => a guess
+    next : usize, //count of spreads tried
+    applied : bool, //spreads[next-1] is on the board
+}
+
+//solves like a human: forced spreads first, otherwise the tower with the fewest spreads left gets one,
+//spreads get taken back when stuck, the skill limits how many guesses can be open at once
+pub struct AiPlayer {
+    max_guesses : usize,
+    stack : Vec<AiLevel>,
+    backtracking : bool,
+    given_up : bool,
+}
+
+impl AiPlayer {
+
+    pub fn new(skill : usize) -> Self {
+        AiPlayer {
+            max_guesses : AI_SKILL_MAX_GUESSES[skill.min(AI_SKILLS_COUNT - 1)],
+            stack : Vec::new(),
+            backtracking : false,
+            given_up : false,
+        }
+    }
+
+    pub fn has_given_up(&self) -> bool {
+        self.given_up
+    }
+
+    //the move to do next on pf, None => solved or given up
+    pub fn calc_next_move(&mut self, pf : &PlayingField) -> Option<AiMove> {
+        if self.given_up || pf.is_solved() {
+            return None;
+        }
+
+        if !self.backtracking {
+            let spreads = pf.calc_ai_spreads();
+            let guesses = self.stack.iter().filter(|level| level.spreads.len()>1).count();
+            if !spreads.is_empty() && (spreads.len()==1 || guesses<self.max_guesses) {
+                let mv = AiMove::new(&spreads[0], true);
+                self.stack.push(AiLevel { spreads, next : 1, applied : true });
+                return Some(mv);
+            }
+            self.backtracking = true;
+        }
+
+        //take back the latest spread and try the next alternative, exhausted levels get dropped
+        loop {
+            let Some(level) = self.stack.last_mut() else {
+                //all own spreads are gone, the ones found on the board go next
+                self.backtracking = false;
+                let mv = pf.calc_ai_clear_move();
+                self.given_up = mv.is_none();
+                return mv;
+            };
+            if level.applied {
+                level.applied = false;
+                return Some(AiMove::new(&level.spreads[level.next-1], false));
+            }
+            if level.next<level.spreads.len() {
+                level.next += 1;
+                level.applied = true;
+                self.backtracking = false;
+                return Some(AiMove::new(&level.spreads[level.next-1], true));
+            }
+            self.stack.pop();
+        }
+    }
+}
+
+
+impl PlayingField {
+
+    //spreads to choose from for the next move, empty => stuck
+    fn calc_ai_spreads(&self) -> Vec<ForcedMove> {
+        if !self.calc_dead_end_xys().is_empty() {
+            return Vec::new();
+        }
+        if let Some(forced_move) = self.calc_forced_move() {
+            return vec![forced_move];
+        }
+
+        //the most constrained tower
+        let mut best : Option<Vec<ForcedMove>> = None;
+        for (tower_index, tower) in self.towers.iter().enumerate() {
+            if tower.flattened_height<=1 {
+                continue;
+            }
+            let spreads = self.calc_ai_tower_spreads(tower_index);
+            if spreads.is_empty() {
+                return spreads;
+            }
+            if best.as_ref().map_or(true, |b| spreads.len()<b.len()) {
+                best = Some(spreads);
+            }
+        }
+        best.unwrap_or_default()
+    }
+
+    //straight: every arm length in every direction, longest first, bendy: every free neighbour tile
+    fn calc_ai_tower_spreads(&self, tower_index : usize) -> Vec<ForcedMove> {
+        let tower = &self.towers[tower_index];
+        let tower_xy = (tower.x as i32, tower.y as i32);
+        let remaining = tower.flattened_height - 1;
+        let mut spreads : Vec<ForcedMove> = Vec::new();
+
+        match self.spread_rule {
+            SpreadRule::Straight => {
+                for dir in DIRS {
+                    for count in (1..=remaining).rev() {
+                        if let Some(xys) = self.calc_flatten_towards_xys(tower_xy, dir, count) {
+                            if xys.len()==count as usize {
+                                spreads.push(ForcedMove { tower_xy, xys });
+                            }
+                        }
+                    }
+                }
+            },
+            SpreadRule::Bendy => {
+                for lin_idx in 0..self.area {
+                    if self.field[lin_idx]!=tower_index as i32 {
+                        continue;
+                    }
+                    for dir in DIRS {
+                        let Some(xy) = self.step_xy(((lin_idx%self.width) as i32, (lin_idx/self.width) as i32), dir) else {
+                            continue;
+                        };
+                        if self.is_free(xy.0, xy.1) && !spreads.iter().any(|s| s.xys[0]==xy) {
+                            spreads.push(ForcedMove { tower_xy, xys : vec![xy] });
+                        }
+                    }
+                }
+            },
+        }
+        spreads
+    }
+
+    //takes back every spread tile of the first tower which has some, locked tiles stay
+    fn calc_ai_clear_move(&self) -> Option<AiMove> {
+        for (tower_index, tower) in self.towers.iter().enumerate() {
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            let xys : Vec<(i32, i32)> = (0..self.area)
+                .map(|i| ((i%self.width) as i32, (i/self.width) as i32))
+                .filter(|xy| *xy!=tower_xy && self.field[lin2d!(xy.0 as usize, xy.1 as usize, self.width)]==tower_index as i32 && !self.is_locked(*xy))
+                .collect();
+            if !xys.is_empty() {
+                return Some(AiMove { tower_xy, xys, flatten : false });
+            }
+        }
+        None
+    }
+
+    //auto test of a generated board (in its start state): lets the player of the given skill loose on it
+    //the board is reset to the start state afterwards
+    pub fn is_solvable_by_ai(&mut self, skill : usize, max_moves : u32) -> bool {
+        let mut ai_player = AiPlayer::new(skill);
+        for _ in 0..max_moves {
+            let Some(mv) = ai_player.calc_next_move(self) else {
+                break;
+            };
+            mv.apply(self);
+        }
+        let solved = self.is_solved();
+        self.reset_to_start_state();
+        solved
+    }
+}
\ No newline at end of file
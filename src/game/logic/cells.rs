@@ -0,0 +1,113 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+use agb::rng::RandomNumberGenerator;
+
+
+pub const OBSTACLE_LEVELS_COUNT : usize = 3; //none, few, many
+const ROCKS_PERCENT_PER_LEVEL : usize = 4; //of the board area
+const LOCKED_PERCENT_PER_LEVEL : usize = 8; //of the tiles towers spread onto
+
+
+//kind of a board tile, apart from the tower covering it and the mask
+#[derive(PartialEq, Clone, Copy)]
+pub enum Cell {
+    Open,
+    Rock, //nothing may cover or cross it
+    Locked, //pre-filled with a tile of its tower (from the solution), can not be taken back
+    Sink(i32), //like a rock, but exactly this many spreads have to end right in front of it
+}
+
+impl PlayingField {
+
+    //not masked out, no rock and no sink, so a tower may cover it
+    pub fn is_open(&self, lin_idx : usize) -> bool {
+        if let Some(m) = &self.mask {
+            if !m.mask[lin_idx] {
+                return false;
+            }
+        }
+        matches!(self.cells[lin_idx], Cell::Open | Cell::Locked)
+    }
+
+    pub fn is_locked(&self, xy : (i32, i32)) -> bool {
+        self.cells[lin2d!(xy.0 as usize, xy.1 as usize, self.width)]==Cell::Locked
+    }
+
+    //replaces all rocks, has to happen before the towers get generated
+    pub fn place_random_rocks(&mut self, rng : &mut RandomNumberGenerator, level : u8) {
+        for cell in self.cells.iter_mut() {
+            if *cell==Cell::Rock {
+                *cell = Cell::Open;
+            }
+        }
+
+        let mut open_tiles : Vec<usize> = (0..self.area).filter(|i| self.is_open(*i)).collect();
+        let count = (self.area*ROCKS_PERCENT_PER_LEVEL*level as usize/100).min(open_tiles.len().saturating_sub(1));
+        crate::util::rng::fisher_yates_shuffle_vec_inplace(&mut open_tiles, rng);
+        for lin_idx in open_tiles.into_iter().take(count) {
+            self.cells[lin_idx] = Cell::Rock;
+        }
+    }
+
+    //pre-fills random tiles of the solution, has to happen after the towers got generated
+    pub fn lock_random_tiles(&mut self, rng : &mut RandomNumberGenerator, level : u8) {
+        let mut spread_tiles : Vec<usize> = (0..self.area)
+            .filter(|i| self.field_solution[*i]!=NO_TOWER && self.field[*i]==NO_TOWER)
+            .collect();
+        let count = spread_tiles.len()*LOCKED_PERCENT_PER_LEVEL*level as usize/100;
+        crate::util::rng::fisher_yates_shuffle_vec_inplace(&mut spread_tiles, rng);
+        for lin_idx in spread_tiles.into_iter().take(count) {
+            self.cells[lin_idx] = Cell::Locked;
+        }
+        self.fill_locked_tiles();
+    }
+
+    //covers every locked tile with its tower, the field must not contain spreads yet
+    pub(super) fn fill_locked_tiles(&mut self) {
+        for lin_idx in 0..self.area {
+            if self.cells[lin_idx]==Cell::Locked {
+                let tower_index = self.field_solution[lin_idx];
+                self.field[lin_idx] = tower_index;
+                self.towers[tower_index as usize].flattened_height -= 1;
+            }
+        }
+    }
+
+    pub(super) fn save_cells(&self, data : &mut Vec<u8>) {
+        let count = self.cells[..self.area].iter().filter(|c| **c!=Cell::Open).count();
+        data.push(((count&0xff00)>>8) as u8);
+        data.push((count&0xff) as u8);
+        for i in 0..self.area {
+            let (kind, value) = match self.cells[i] {
+                Cell::Open => continue,
+                Cell::Rock => (1, 0),
+                Cell::Locked => (2, 0),
+                Cell::Sink(count) => (3, count as u8),
+            };
+            data.push((i%self.width) as u8);
+            data.push((i/self.width) as u8);
+            data.push(kind);
+            data.push(value);
+        }
+    }
+
+    pub(super) fn load_cells(&mut self, data : &[u8]) -> usize {
+        let mut di : usize = 0;
+        self.cells = [Cell::Open; MAX_PLAYING_FIELD_AREA];
+        let count = (data[di] as usize)<<8 | (data[di+1] as usize); di+=2;
+        for _ in 0..count {
+            let lin_idx = lin2d!(data[di] as usize, data[di+1] as usize, self.width);
+            self.cells[lin_idx] = match data[di+2] {
+                1 => Cell::Rock,
+                2 => Cell::Locked,
+                _ => Cell::Sink(data[di+3] as i32),
+            };
+            di+=4;
+        }
+        di
+    }
+}
\ No newline at end of file
@@ -0,0 +1,141 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+pub const MAX_CLUE_ARM_LEN : i32 = 4;
+const MAX_CLUE_TOWER_HEIGHT : i32 = 9; //the number stays a single digit, leaving room for the clue glyph
+const CLUE_SOLVER_MAX_MOVES : u32 = 300;
+
+
+//constraint on the spread directions of a tower, only used with straight spreads
+#[derive(PartialEq, Clone, Copy)]
+pub enum Clue {
+    None,
+    Horizontal, //spreads only to the left and right
+    Vertical, //spreads only up and down
+    Arm { dir_index : usize, len : i32 }, //the arm towards DIRS[dir_index] (left, right, up, down) is exactly len long
+}
+
+impl Clue {
+
+    //false if the tile dist steps away from the origin in dir may not be covered
+    pub fn allows(&self, dir : (i32, i32), dist : i32) -> bool {
+        match *self {
+            Clue::None => true,
+            Clue::Horizontal => dir.1==0,
+            Clue::Vertical => dir.0==0,
+            Clue::Arm { dir_index, len } => dir!=DIRS[dir_index] || dist<=len,
+        }
+    }
+}
+
+impl PlayingField {
+
+    //count of tiles of the tower following each other from tower_xy in dir
+    fn calc_arm_len(&self, field : &[i32], tower_xy : (i32, i32), dir : (i32, i32)) -> i32 {
+        let tower_index = field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, self.width)];
+        let mut len = 0;
+        let mut xy = tower_xy;
+        while let Some(next_xy) = self.step_xy(xy, dir) {
+            if next_xy==tower_xy || field[lin2d!(next_xy.0 as usize, next_xy.1 as usize, self.width)]!=tower_index {
+                break;
+            }
+            len += 1;
+            xy = next_xy;
+        }
+        len
+    }
+
+    //arms with an exact length clue have to be completed, the other clues are enforced while spreading
+    pub fn are_clues_satisfied(&self) -> bool {
+        self.towers.iter().all(|tower| match tower.clue {
+            Clue::Arm { dir_index, len } => self.calc_arm_len(&self.field, (tower.x as i32, tower.y as i32), DIRS[dir_index])==len,
+            _ => true,
+        })
+    }
+
+    //gives towers clues taken from the solution until the solver finds no other layout (in time)
+    //has to happen after the towers got generated
+    pub fn add_clue_towers(&mut self) {
+        if self.spread_rule==SpreadRule::Bendy {
+            return;
+        }
+        let solution = self.field_solution[..self.area].to_vec();
+        while let Some(other) = self.calc_other_solution(CLUE_SOLVER_MAX_MOVES) {
+            let added = (0..self.towers.len()).any(|tower_index| {
+                let differs = (0..self.area).any(|i| (solution[i]==tower_index as i32)!=(other[i]==tower_index as i32));
+                differs && self.add_clue(tower_index, &solution, &other)
+            });
+            if !added {
+                break;
+            }
+        }
+    }
+
+    //clue which the solution fulfills, but the other layout does not, false => none found
+    fn add_clue(&mut self, tower_index : usize, solution : &[i32], other : &[i32]) -> bool {
+        let tower = &self.towers[tower_index];
+        if tower.clue!=Clue::None || tower.height>MAX_CLUE_TOWER_HEIGHT {
+            return false;
+        }
+        let tower_xy = (tower.x as i32, tower.y as i32);
+
+        let lens : Vec<(i32, i32)> = DIRS.iter().map(|dir| (self.calc_arm_len(solution, tower_xy, *dir), self.calc_arm_len(other, tower_xy, *dir))).collect();
+        if let Some(dir_index) = (0..DIRS.len()).find(|i| lens[*i].0!=lens[*i].1 && lens[*i].0<=MAX_CLUE_ARM_LEN) {
+            self.towers[tower_index].clue = Clue::Arm { dir_index, len : lens[dir_index].0 };
+            return true;
+        }
+
+        //arms too long for a length clue, the axis may still tell the layouts apart
+        let horizontal = (lens[0].0>0 || lens[1].0>0, lens[0].1>0 || lens[1].1>0);
+        let vertical = (lens[2].0>0 || lens[3].0>0, lens[2].1>0 || lens[3].1>0);
+        if !vertical.0 && vertical.1 {
+            self.towers[tower_index].clue = Clue::Horizontal;
+            true
+        } else if !horizontal.0 && horizontal.1 {
+            self.towers[tower_index].clue = Clue::Vertical;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(super) fn save_clues(&self, data : &mut Vec<u8>) {
+        let count = self.towers.iter().filter(|t| t.clue!=Clue::None).count();
+        data.push(((count&0xff00)>>8) as u8);
+        data.push((count&0xff) as u8);
+        for (tower_index, tower) in self.towers.iter().enumerate() {
+            let (kind, dir_index, len) = match tower.clue {
+                Clue::None => continue,
+                Clue::Horizontal => (1, 0, 0),
+                Clue::Vertical => (2, 0, 0),
+                Clue::Arm { dir_index, len } => (3, dir_index as u8, len as u8),
+            };
+            data.push(((tower_index&0xff00)>>8) as u8);
+            data.push((tower_index&0xff) as u8);
+            data.push(kind);
+            data.push(dir_index);
+            data.push(len);
+        }
+    }
+
+    pub(super) fn load_clues(&mut self, data : &[u8]) -> usize {
+        let mut di : usize = 0;
+        let count = (data[di] as usize)<<8 | (data[di+1] as usize); di+=2;
+        for _ in 0..count {
+            let tower_index = (data[di] as usize)<<8 | (data[di+1] as usize);
+            self.towers[tower_index].clue = match data[di+2] {
+                1 => Clue::Horizontal,
+                2 => Clue::Vertical,
+                _ => Clue::Arm { dir_index : data[di+3] as usize, len : data[di+4] as i32 },
+            };
+            di+=5;
+        }
+        di
+    }
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+
+impl PlayingField {
+
+    //tiles which make the board unsolvable without deflattening:
+    //free tiles no tower can reach anymore, origins of towers whose remaining height exceeds their free space
+    //and sinks in front of which too many spreads end
+    pub fn calc_dead_end_xys(&self) -> Vec<(i32, i32)> {
+        if self.spread_rule==SpreadRule::Bendy {
+            return self.calc_bendy_dead_end_xys();
+        }
+
+        let mut xys = Vec::new();
+
+        for tower in self.towers.iter() {
+            let remaining = tower.flattened_height - 1;
+            if remaining<=0 {
+                continue;
+            }
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            if self.calc_free_space(tower_xy, remaining).iter().sum::<i32>()<remaining {
+                xys.push(tower_xy);
+            }
+        }
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.is_free(x, y) && self.calc_spreads_reaching((x, y)).is_empty() {
+                    xys.push((x, y));
+                }
+                if self.calc_sink_remaining((x, y))<0 {
+                    xys.push((x, y));
+                }
+            }
+        }
+
+        xys
+    }
+}
\ No newline at end of file
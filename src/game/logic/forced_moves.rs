@@ -0,0 +1,122 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+
+//a spread every solution reachable without deflattening has to contain
+//(the solver also uses it for any spread it tries)
+#[derive(Clone)]
+pub struct ForcedMove {
+    pub tower_xy : (i32, i32),
+    pub xys : Vec<(i32, i32)>,
+}
+
+impl PlayingField {
+
+    //inside, not masked out, no rock and not covered by a tower
+    pub fn is_free(&self, x : i32, y : i32) -> bool {
+        if !self.is_inside(x, y) {
+            return false;
+        }
+        let lin_idx = lin2d!(x as usize, y as usize, self.width);
+        self.is_open(lin_idx) && self.field[lin_idx]==NO_TOWER
+    }
+
+    //first forced spread found, None => no tower is forced (or the board is a dead end)
+    //only derived for straight spreads
+    pub fn calc_forced_move(&self) -> Option<ForcedMove> {
+        if self.spread_rule==SpreadRule::Bendy {
+            return None;
+        }
+        self.calc_forced_move_by_space().or_else(|| self.calc_forced_move_by_reach())
+    }
+
+    //a tower whose free space in the other directions does not suffice for its remaining height
+    //must spread the rest into the last direction, e.g. a tower boxed in on three sides
+    fn calc_forced_move_by_space(&self) -> Option<ForcedMove> {
+        for tower in self.towers.iter() {
+            let remaining = tower.flattened_height - 1;
+            if remaining<=0 {
+                continue;
+            }
+
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            let space = self.calc_free_space(tower_xy, remaining);
+            let total : i32 = space.iter().sum();
+            if total<remaining {
+                continue; //dead end, nothing is forced anymore
+            }
+
+            for (i, dir) in DIRS.iter().enumerate() {
+                let needed = remaining - (total - space[i]);
+                if needed>0 {
+                    if let Some(xys) = self.calc_flatten_towards_xys(tower_xy, *dir, needed) {
+                        return Some(ForcedMove { tower_xy, xys });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    //an empty tile only one tower can still reach must be covered by it
+    fn calc_forced_move_by_reach(&self) -> Option<ForcedMove> {
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if !self.is_free(x, y) {
+                    continue;
+                }
+
+                let mut reaching = self.calc_spreads_reaching((x, y));
+                if reaching.len()==1 {
+                    return reaching.pop();
+                }
+            }
+        }
+        None
+    }
+
+    //free tiles (max. remaining) the tower at tower_xy could still spread into, per direction of DIRS
+    pub(super) fn calc_free_space(&self, tower_xy : (i32, i32), remaining : i32) -> [i32; 4] {
+        let mut space = [0; 4];
+        for (i, dir) in DIRS.iter().enumerate() {
+            space[i] = self.calc_flatten_towards_xys(tower_xy, *dir, remaining).map_or(0, |xys| xys.len() as i32);
+        }
+        space
+    }
+
+    //every spread which would cover the free tile at xy, at most one per tower
+    pub(super) fn calc_spreads_reaching(&self, xy : (i32, i32)) -> Vec<ForcedMove> {
+        let mut reaching = Vec::new();
+        for dir in DIRS {
+            //first covered tile in dir, its tower may reach xy if its origin is in line
+            let Some((mut sx, mut sy)) = self.step_xy(xy, dir) else {
+                continue;
+            };
+            while self.is_free(sx, sy) && (sx, sy)!=xy {
+                let Some(sxy) = self.step_xy((sx, sy), dir) else {
+                    break;
+                };
+                (sx, sy) = sxy;
+            }
+            if !self.is_inside(sx, sy) || self.is_free(sx, sy) {
+                continue;
+            }
+            let tower_idx = self.field[lin2d!(sx as usize, sy as usize, self.width)];
+            if tower_idx==NO_TOWER {
+                continue; //masked out or a rock
+            }
+            let tower = &self.towers[tower_idx as usize];
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            if let Some(xys) = self.calc_flatten_xys(tower_xy, xy) {
+                reaching.push(ForcedMove { tower_xy, xys });
+            }
+        }
+        reaching
+    }
+}
\ No newline at end of file
@@ -1,9 +1,23 @@
+mod ai_player;
+mod cells;
+mod clues;
+mod dead_ends;
 mod defs;
+mod forced_moves;
+mod notes;
 mod playing_field_mask;
 mod playing_field;
+mod sinks;
+mod solver;
+mod spread_rule;
 mod tower;
 
+pub use ai_player::*;
+pub use cells::*;
+pub use clues::*;
 pub use defs::*;
+pub use notes::*;
 pub use playing_field_mask::*;
 pub use playing_field::*;
+pub use spread_rule::*;
 pub use tower::*;
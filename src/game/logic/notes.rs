@@ -0,0 +1,81 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+
+//pencil mark of an empty tile, cleared as soon as a tower covers the tile
+#[derive(PartialEq, Clone, Copy)]
+pub enum Note {
+    None,
+    Tower(usize), //the tile must belong to the tower with this index
+    Blocked, //no tower can cover the tile
+}
+
+impl PlayingField {
+
+    pub fn note(&self, x : usize, y : usize) -> Note {
+        self.notes[lin2d!(x, y, self.width)]
+    }
+
+    //steps the note of the free tile at xy through none, every tower still reaching it and blocked
+    //returns false if the tile is not free
+    pub fn cycle_note(&mut self, xy : (i32, i32)) -> bool {
+        if !self.is_free(xy.0, xy.1) {
+            return false;
+        }
+
+        let mut choices = Vec::new();
+        choices.push(Note::None);
+        if self.spread_rule==SpreadRule::Bendy {
+            choices.extend(self.calc_bendy_towers_reaching(xy).into_iter().map(Note::Tower));
+        } else {
+            for spread in self.calc_spreads_reaching(xy) {
+                let tower_idx = self.field[lin2d!(spread.tower_xy.0 as usize, spread.tower_xy.1 as usize, self.width)];
+                choices.push(Note::Tower(tower_idx as usize));
+            }
+        }
+        choices.push(Note::Blocked);
+
+        let lin_idx = lin2d!(xy.0 as usize, xy.1 as usize, self.width);
+        //a tower which can not reach the tile anymore is skipped
+        let next = choices.iter().position(|n| *n==self.notes[lin_idx]).map_or(1, |i| (i+1)%choices.len());
+        self.notes[lin_idx] = choices[next];
+        true
+    }
+
+    pub fn clear_notes(&mut self) {
+        self.notes = [Note::None; MAX_PLAYING_FIELD_AREA];
+    }
+
+    pub(super) fn save_notes(&self, data : &mut Vec<u8>) {
+        let count = self.notes[..self.area].iter().filter(|n| **n!=Note::None).count();
+        data.push(((count&0xff00)>>8) as u8);
+        data.push((count&0xff) as u8);
+        for i in 0..self.area {
+            let value = match self.notes[i] {
+                Note::None => continue,
+                Note::Tower(tower_idx) => tower_idx,
+                Note::Blocked => 0xffff,
+            };
+            data.push((i%self.width) as u8);
+            data.push((i/self.width) as u8);
+            data.push(((value&0xff00)>>8) as u8);
+            data.push((value&0xff) as u8);
+        }
+    }
+
+    pub(super) fn load_notes(&mut self, data : &[u8]) -> usize {
+        let mut di : usize = 0;
+        self.clear_notes();
+        let count = (data[di] as usize)<<8 | (data[di+1] as usize); di+=2;
+        for _ in 0..count {
+            let lin_idx = lin2d!(data[di] as usize, data[di+1] as usize, self.width);
+            let value = (data[di+2] as usize)<<8 | (data[di+3] as usize);
+            self.notes[lin_idx] = if value==0xffff {Note::Blocked} else {Note::Tower(value)};
+            di+=4;
+        }
+        di
+    }
+}
\ No newline at end of file
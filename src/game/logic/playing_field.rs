@@ -9,6 +9,8 @@ use alloc::collections::BTreeSet;
 
 use agb::rng::RandomNumberGenerator;
 
+const SOLVER_HINT_MAX_MOVES : u32 = 400;
+
 pub struct PlayingField {
     pub width: usize,
     pub height: usize,
@@ -17,6 +19,10 @@ pub struct PlayingField {
     pub field: [i32; MAX_PLAYING_FIELD_AREA],
     pub field_solution: [i32; MAX_PLAYING_FIELD_AREA],
     pub mask : Option<PlayingFieldMask>,
+    pub spread_rule : SpreadRule,
+    pub wrap_around : bool, //spreads leaving an edge re-enter at the opposite edge
+    pub notes : [Note; MAX_PLAYING_FIELD_AREA],
+    pub cells : [Cell; MAX_PLAYING_FIELD_AREA],
 }
 
 impl PlayingField {
@@ -31,6 +37,10 @@ impl PlayingField {
             field: [NO_TOWER; MAX_PLAYING_FIELD_AREA],
             field_solution: [NO_TOWER; MAX_PLAYING_FIELD_AREA],
             mask,
+            spread_rule: SpreadRule::Straight,
+            wrap_around: false,
+            notes: [Note::None; MAX_PLAYING_FIELD_AREA],
+            cells: [Cell::Open; MAX_PLAYING_FIELD_AREA],
         }
     }
 
@@ -39,7 +49,11 @@ impl PlayingField {
         for xy in 0..self.area {
             self.field[xy] = NO_TOWER;
             self.field_solution[xy] = NO_TOWER;
+            if self.cells[xy]==Cell::Locked {
+                self.cells[xy] = Cell::Open; //rocks and sinks stay, they are placed before the towers
+            }
         }
+        self.clear_notes();
     }
 
     pub fn init_with_random_towers(&mut self, rng : &mut RandomNumberGenerator) {
@@ -48,12 +62,25 @@ impl PlayingField {
             assert!(m.width==self.width && m.height==self.height);
         }
 
+        if self.spread_rule==SpreadRule::Bendy {
+            self.init_with_random_bendy_towers(rng);
+        } else {
+            self.init_with_random_straight_towers(rng);
+        }
+
+        for (tower_index, tower) in self.towers.iter().enumerate()
+        {
+            self.field[lin2d!(tower.x, tower.y, self.width)]=tower_index as i32;
+        }
+    }
+
+    //every tower spreads into 1 to 4 random directions from a random origin
+    fn init_with_random_straight_towers(&mut self, rng : &mut RandomNumberGenerator) {
+
         let mut empty_tiles_set: BTreeSet<usize> = BTreeSet::new();
         for lin_xy in 0..self.area {
-            if let Some(m) = &self.mask {
-                if !m.mask[lin_xy] {
-                    continue;
-                }
+            if !self.is_open(lin_xy) {
+                continue;
             }
             empty_tiles_set.insert(lin_xy);
         }
@@ -89,25 +116,15 @@ impl PlayingField {
                 let mut px = new_tower_x as i32;
                 let mut py = new_tower_y as i32;
 
-                //see how far we can spread
+                //see how far we can spread (on wrap-around boards the own origin stops it at the latest)
                 let mut ml : i32 = 0; //ml = max length
-                loop {
-                    px+=dvx;
-                    py+=dvy;
-
-                    if px<0 || px>=(self.width as i32) || py<0 || py>=(self.height as i32) {
-                        break;
-                    }
+                while let Some(pxy) = self.step_xy((px, py), (dvx, dvy)) {
+                    (px, py) = pxy;
 
                     let lin_xy = lin2d!(px as usize, py as usize, self.width);
-                    if self.field_solution[lin_xy]!=NO_TOWER {
+                    if self.field_solution[lin_xy]!=NO_TOWER || !self.is_open(lin_xy) {
                         break;
                     }
-                    if let Some(m) = &self.mask {
-                        if !m.mask[lin_xy] {
-                            break;
-                        }
-                    }
 
                     ml+=1;
                 }
@@ -126,8 +143,7 @@ impl PlayingField {
                 px = new_tower_x as i32;
                 py = new_tower_y as i32;
                 for _ in 0..l {
-                    px+=dvx;
-                    py+=dvy;
+                    (px, py) = self.step_xy((px, py), (dvx, dvy)).unwrap();
 
                     let lin_xy = lin2d!(px as usize, py as usize, self.width);
 
@@ -144,15 +160,44 @@ impl PlayingField {
                 height: new_tower_height,
                 flattened_height: new_tower_height,
                 bounds: Rect::new(Vector2D::new(new_tower_x as i32, new_tower_y as i32), Vector2D::new(1i32, 1i32)),
+                clue: Clue::None,
             });
             new_tower_index += 1;
         }
+    }
 
-        for (tower_index, tower) in self.towers.iter().enumerate()
-        {
-            self.field[lin2d!(tower.x, tower.y, self.width)]=tower_index as i32;
+
+    //sets up a fixed board, e.g. for the examples in the help pages
+    //field holds the tower index of every tile, towers the origin and flattened height of every tower
+    //the tower heights follow from the spread tiles, solution is the solved layout used for hints and coloring
+    pub fn init_with_layout(&mut self, field : &[i32], solution : &[i32], towers : &[(usize, usize, i32)]) {
+        self.clear();
+        assert!(field.len()==self.area && solution.len()==self.area);
+        self.spread_rule = SpreadRule::Straight;
+        self.wrap_around = false;
+        self.cells = [Cell::Open; MAX_PLAYING_FIELD_AREA];
+
+        for (tower_index, t) in towers.iter().enumerate() {
+            let spread_tiles = field.iter().filter(|i| **i==tower_index as i32).count() as i32 - 1;
+            self.towers.push(Tower {
+                x: t.0,
+                y: t.1,
+                height: t.2 + spread_tiles,
+                flattened_height: t.2,
+                bounds: Rect::new(Vector2D::new(t.0 as i32, t.1 as i32), Vector2D::new(1i32, 1i32)),
+                clue: Clue::None,
+            });
+        }
+
+        for xy in 0..self.area {
+            self.field[xy] = field[xy];
+            self.field_solution[xy] = solution[xy];
         }
 
+        for (x, y, _) in towers.iter() {
+            self.recalc_tower_bounds((*x as i32, *y as i32));
+        }
+        assert!(self.is_valid_layout(field) && self.is_valid_layout(solution));
     }
 
 
@@ -172,10 +217,6 @@ impl PlayingField {
             data.push(tower.y as u8);
             data.push(tower.height as u8);
             data.push(tower.flattened_height as u8);
-            data.push(tower.bounds.position.x as u8);
-            data.push(tower.bounds.position.y as u8);
-            data.push(tower.bounds.size.x as u8);
-            data.push(tower.bounds.size.y as u8);
         }
 
         for i in 0..self.width*self.height {
@@ -210,6 +251,13 @@ impl PlayingField {
             }
         }
 
+        data.push(self.spread_rule.as_u8());
+        data.push(if self.wrap_around {1} else {0});
+
+        self.save_cells(&mut data);
+        self.save_clues(&mut data);
+        self.save_notes(&mut data);
+
         data
     }
 
@@ -230,11 +278,10 @@ impl PlayingField {
                 y : data[di+1] as usize,
                 height : data[di+2] as i32,
                 flattened_height : data[di+3] as i32,
-                bounds : Rect::<i32> { 
-                    position: Vector2D::new(data[di+4] as i32, data[di+5] as i32), 
-                    size: Vector2D::new(data[di+6] as i32, data[di+7] as i32) }
+                bounds : Rect::new(Vector2D::new(data[di+0] as i32, data[di+1] as i32), Vector2D::new(1i32, 1i32)),
+                clue : Clue::None,
             });
-            di+=8;  
+            di+=4;
         }
 
         self.field = [NO_TOWER; MAX_PLAYING_FIELD_AREA];
@@ -274,6 +321,19 @@ impl PlayingField {
             self.mask = Some(m)
         }
 
+        self.spread_rule = SpreadRule::from_u8(data[di]); di+=1;
+        self.wrap_around = data[di]!=0; di+=1;
+
+        di += self.load_cells(&data[di..]);
+        di += self.load_clues(&data[di..]);
+        di += self.load_notes(&data[di..]);
+
+        //bounds are not saved, on wrap-around boards they may reach beyond the board edges
+        for tower_index in 0..self.towers.len() {
+            let tower = &self.towers[tower_index];
+            self.recalc_tower_bounds((tower.x as i32, tower.y as i32));
+        }
+
         di
 
     }   
@@ -287,6 +347,11 @@ impl PlayingField {
             tower.flattened_height = tower.height;
             self.field[lin2d!(tower.x, tower.y, self.width)] = tower_index as i32;
         }
+        self.fill_locked_tiles();
+        for tower_index in 0..self.towers.len() {
+            let tower = &self.towers[tower_index];
+            self.recalc_tower_bounds((tower.x as i32, tower.y as i32));
+        }
     }
 
     pub fn set_to_solution_state(&mut self) {
@@ -296,6 +361,7 @@ impl PlayingField {
         for tower in self.towers.iter_mut() {
             tower.flattened_height = 1
         }
+        self.clear_notes();
     }
 
     pub fn is_solved(&self) -> bool {
@@ -304,7 +370,73 @@ impl PlayingField {
                 return false;
             }
         }
-        return true;
+        return self.are_clues_satisfied() && self.are_sinks_satisfied();
+    }
+
+    //index of a tower which differs from the solution, true => it covers a tile it must give back (deflatten)
+    //false => it has to be spread further (flatten), None => the field matches the solution
+    //a layout the solver completes the current field to is preferred over the generated solution
+    pub fn calc_hint(&mut self) -> Option<(usize, bool)> {
+        let found_solution = self.calc_solution(SOLVER_HINT_MAX_MOVES);
+        let solution = found_solution.as_deref().unwrap_or(&self.field_solution[..self.area]);
+
+        for xy in 0..self.area {
+            let t = self.field[xy];
+            if t!=NO_TOWER && t!=solution[xy] {
+                return Some((t as usize, true));
+            }
+        }
+        for xy in 0..self.area {
+            let t = solution[xy];
+            if self.field[xy]==NO_TOWER && t!=NO_TOWER {
+                return Some((t as usize, false));
+            }
+        }
+        None
+    }
+
+    //greedy graph coloring of the towers, touching towers (in the solution) never share a color
+    //as long as count_colors suffices, every tower prefers color tower_index%count_colors
+    pub fn calc_tower_coloring(&self, count_colors : usize) -> Vec<usize> {
+        let count_towers = self.towers.len();
+
+        let mut neighbours = vec![BTreeSet::<usize>::new(); count_towers];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let t = self.field_solution[lin2d!(x,y,self.width)];
+                if t==NO_TOWER {
+                    continue;
+                }
+                for (nx, ny) in [(x+1, y), (x, y+1)] {
+                    if nx>=self.width || ny>=self.height {
+                        continue;
+                    }
+                    let n = self.field_solution[lin2d!(nx,ny,self.width)];
+                    if n!=NO_TOWER && n!=t {
+                        neighbours[t as usize].insert(n as usize);
+                        neighbours[n as usize].insert(t as usize);
+                    }
+                }
+            }
+        }
+
+        let mut coloring = vec![usize::MAX; count_towers];
+        let mut used = vec![false; count_colors];
+        for tower_index in 0..count_towers {
+            used.iter_mut().for_each(|u| *u=false);
+            for n in neighbours[tower_index].iter() {
+                if coloring[*n]!=usize::MAX {
+                    used[coloring[*n]] = true;
+                }
+            }
+            let preferred = tower_index%count_colors;
+            coloring[tower_index] = (0..count_colors)
+                .map(|c| (preferred+c)%count_colors)
+                .find(|c| !used[*c])
+                .unwrap_or(preferred);
+        }
+
+        coloring
     }
 
     pub fn is_inside(&self, x : i32, y : i32) -> bool {
@@ -314,6 +446,29 @@ impl PlayingField {
         xy.0>=0 && xy.0<self.width as i32 && xy.1>=0 && xy.1<self.height as i32
     }
 
+    //neighbour of xy in dir, on wrap-around boards leaving an edge re-enters at the opposite edge
+    //None => outside of the board
+    pub fn step_xy(&self, xy : (i32,i32), dir : (i32,i32)) -> Option<(i32,i32)> {
+        let (x, y) = (xy.0 + dir.0, xy.1 + dir.1);
+        if self.wrap_around {
+            Some((x.rem_euclid(self.width as i32), y.rem_euclid(self.height as i32)))
+        } else if self.is_inside(x, y) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    //directions along which pointing_xy may be reached from tower_xy, both ways round on wrap-around boards
+    fn calc_line_dirs(&self, tower_xy : (i32,i32), pointing_xy : (i32,i32)) -> Vec<(i32,i32)> {
+        let dir = ((pointing_xy.0 - tower_xy.0).signum(), (pointing_xy.1 - tower_xy.1).signum());
+        if self.wrap_around {
+            vec![dir, (-dir.0, -dir.1)]
+        } else {
+            vec![dir]
+        }
+    }
+
     pub fn calc_flatten_xys(&self, tower_xy : (i32,i32), pointing_xy : (i32,i32)) -> Option<Vec<(i32,i32)>> {
         if !self.is_inside_xy(tower_xy) || !self.is_inside_xy(pointing_xy) {
             return None;
@@ -341,8 +496,12 @@ impl PlayingField {
             return None;
         }
 
-        let dx : i32 = (pointing_xy.0 - tower_xy.0).signum();
-        let dy : i32 = (pointing_xy.1 - tower_xy.1).signum();
+        self.calc_line_dirs(tower_xy, pointing_xy).into_iter().find_map(|dir| self.calc_flatten_xys_along(tower_xy, pointing_xy, dir))
+    }
+
+    fn calc_flatten_xys_along(&self, tower_xy : (i32,i32), pointing_xy : (i32,i32), dir : (i32,i32)) -> Option<Vec<(i32,i32)>> {
+        let tower_index = self.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, self.width)];
+        let tower = &self.towers[tower_index as usize];
 
         let mut x : i32 = tower_xy.0;
         let mut y : i32 = tower_xy.1;
@@ -352,30 +511,32 @@ impl PlayingField {
         let mut flatten_poses : Vec<(i32,i32)> = Vec::with_capacity(self.width.max(self.height));
 
         let mut height_contingent : i32 = tower.flattened_height - 1;
+        let mut dist : i32 = 0;
 
         loop {
-            x += dx;
-            y += dy;
-            if !self.is_inside(x, y) {
-                return None;
+            (x, y) = self.step_xy((x, y), dir)?;
+            if (x, y)==tower_xy {
+                return None; //once around the board
             }
+            dist += 1;
 
             let xy_lin_idx = lin2d!(x as usize, y as usize, self.width);
 
-            if let Some(m) = &self.mask {
-                if !m.mask[xy_lin_idx] {
-                    return None;
-                }
+            if !self.is_open(xy_lin_idx) {
+                return None;
             }
 
             let xy_tower_index = self.field[xy_lin_idx];
 
             if xy_tower_index == NO_TOWER {
+                if !tower.clue.allows(dir, dist) {
+                    return None;
+                }
                 only_empty_allowed = true; // only no / empty tower tiles allowed from now on
                 flatten_poses.push((x,y));
                 height_contingent-=1;
             }
-            else if xy_tower_index != tower_index || only_empty_allowed {
+            else if xy_tower_index != tower_index || (only_empty_allowed && self.cells[xy_lin_idx]!=Cell::Locked) {
                 return None;
             }
 
@@ -426,30 +587,35 @@ impl PlayingField {
         let mut flatten_poses : Vec<(i32,i32)> = Vec::with_capacity(self.width.max(self.height));
 
         let mut height_contingent : i32 = (tower.flattened_height - 1).min(count);
+        let mut dist : i32 = 0;
 
         loop {
-            x += dx;
-            y += dy;
-            if !self.is_inside(x, y) {
+            let Some(xy) = self.step_xy((x, y), (dx, dy)) else {
                 break;
+            };
+            if xy==tower_xy {
+                break; //once around the board
             }
+            (x, y) = xy;
+            dist += 1;
 
             let xy_lin_idx = lin2d!(x as usize, y as usize, self.width);
 
-            if let Some(m) = &self.mask {
-                if !m.mask[xy_lin_idx] {
-                    break;
-                }
+            if !self.is_open(xy_lin_idx) {
+                break;
             }
 
             let xy_tower_index = self.field[xy_lin_idx];
 
             if xy_tower_index == NO_TOWER {
+                if !tower.clue.allows((dx, dy), dist) {
+                    break;
+                }
                 only_empty_allowed = true; // only no / empty tower tiles allowed from now on
                 flatten_poses.push((x,y));
                 height_contingent-=1;
             }
-            else if xy_tower_index != tower_index || only_empty_allowed {
+            else if xy_tower_index != tower_index || (only_empty_allowed && self.cells[xy_lin_idx]!=Cell::Locked) {
                 break;
             }
 
@@ -476,6 +642,7 @@ impl PlayingField {
 
             for fxy in to_flatten_xys.iter() {
                 self.field[lin2d!(fxy.0 as usize, fxy.1 as usize, self.width)] = tower_index;
+                self.notes[lin2d!(fxy.0 as usize, fxy.1 as usize, self.width)] = Note::None;
             }
 
             self.recalc_tower_bounds(tower_xy);
@@ -514,32 +681,45 @@ impl PlayingField {
         //    return None;
         //}
 
-        let dx : i32 = (pointing_xy.0 - tower_xy.0).signum();
-        let dy : i32 = (pointing_xy.1 - tower_xy.1).signum();
+        //the arm of the tower pointing_xy lies on
+        let is_on_arm = |dir : (i32,i32)| {
+            let mut xy = tower_xy;
+            while let Some(next_xy) = self.step_xy(xy, dir) {
+                if next_xy==tower_xy || self.field[lin2d!(next_xy.0 as usize, next_xy.1 as usize, self.width)]!=tower_index {
+                    return false;
+                }
+                if next_xy==pointing_xy {
+                    return true;
+                }
+                xy = next_xy;
+            }
+            false
+        };
+        let dir = self.calc_line_dirs(tower_xy, pointing_xy).into_iter().find(|dir| is_on_arm(*dir))?;
 
-        let mut x : i32 = pointing_xy.0;
-        let mut y : i32 = pointing_xy.1;
+        let mut xy = pointing_xy;
 
         let mut deflatten_poses : Vec<(i32,i32)> = Vec::with_capacity(self.width.max(self.height));
 
-        deflatten_poses.push((x,y));
+        //locked tiles are never given back
+        if !self.is_locked(xy) {
+            deflatten_poses.push(xy);
+        }
 
-        loop {
-            x += dx;
-            y += dy;
-            if !self.is_inside(x, y) {
+        while let Some(next_xy) = self.step_xy(xy, dir) {
+            if next_xy==tower_xy || self.field[lin2d!(next_xy.0 as usize, next_xy.1 as usize, self.width)]!=tower_index {
                 break;
             }
 
-            let xy_tower_index = self.field[lin2d!(x as usize, y as usize, self.width)];
-            if xy_tower_index!=tower_index {
-                break;
+            if !self.is_locked(next_xy) {
+                deflatten_poses.push(next_xy);
             }
-
-            deflatten_poses.push((x,y));
-
+            xy = next_xy;
         }
         
+        if deflatten_poses.is_empty() {
+            return None;
+        }
         return Some(deflatten_poses);
 
     }
@@ -573,22 +753,11 @@ impl PlayingField {
         let mut deflatten_poses : Vec<(i32,i32)> = Vec::with_capacity(self.width.max(self.height));
 
         //seek to end
-        loop {
-            x += dx;
-            y += dy;
-            if !self.is_inside(x, y) {
-                x -= dx;
-                y -= dy;
-                break;
-            }
-
-            let xy_tower_index = self.field[lin2d!(x as usize, y as usize, self.width)];
-            if xy_tower_index!=tower_index {
-                x -= dx;
-                y -= dy;
+        while let Some(xy) = self.step_xy((x, y), (dx, dy)) {
+            if xy==tower_xy || self.field[lin2d!(xy.0 as usize, xy.1 as usize, self.width)]!=tower_index {
                 break;
             }
-
+            (x, y) = xy;
         }
 
         if x==tower_xy.0 && y==tower_xy.1 {
@@ -596,19 +765,23 @@ impl PlayingField {
         }
 
         loop {
-            deflatten_poses.push((x,y));
-            if deflatten_poses.len() >= count as usize {
-                break;
+            if !self.is_locked((x,y)) {
+                deflatten_poses.push((x,y));
+                if deflatten_poses.len() >= count as usize {
+                    break;
+                }
             }
 
-            x -= dx;
-            y -= dy;
+            (x, y) = self.step_xy((x, y), (-dx, -dy)).unwrap(); //the way back is always on the board
 
             if x==tower_xy.0 && y==tower_xy.1 {
                 break;
             }
         }
         
+        if deflatten_poses.is_empty() {
+            return None;
+        }
         return Some(deflatten_poses);
 
     }
@@ -650,29 +823,27 @@ impl PlayingField {
         if tower_index == NO_TOWER {
             return;
         }
-        
 
+        if self.spread_rule==SpreadRule::Bendy {
+            let (min_x, min_y, max_x, max_y) = self.calc_bendy_tower_bounds(tower_index);
+            let tower = &mut self.towers[tower_index as usize];
+            tower.bounds = Rect::new(Vector2D::new(min_x, min_y), Vector2D::new(max_x-min_x+1, max_y-min_y+1));
+            return;
+        }
 
-        let mut x : i32 = tower_xy.0;
-        let mut y : i32 = tower_xy.1;
-
-        let mut min_x : i32 = x;
-        let mut min_y : i32 = y;
-        let mut max_x : i32 = x;
-        let mut max_y : i32 = y;
+        //length of the arm in every direction
+        let mut arms = [0i32; 4];
 
-        for (dx,dy) in [(1,0), (-1,0), (0,1), (0,-1)] {
-            x = tower_xy.0;
-            y = tower_xy.1;
+        for (i, dir) in [(1,0), (-1,0), (0,1), (0,-1)].into_iter().enumerate() {
+            let mut xy = tower_xy;
 
-            loop {
-                x += dx;
-                y += dy;
-                if !self.is_inside(x, y) {
+            while let Some(next_xy) = self.step_xy(xy, dir) {
+                if next_xy==tower_xy {
                     break;
                 }
+                xy = next_xy;
 
-                let xy_lin_idx = lin2d!(x as usize, y as usize, self.width);
+                let xy_lin_idx = lin2d!(xy.0 as usize, xy.1 as usize, self.width);
 
                 if let Some(m) = &self.mask {
                     if !m.mask[xy_lin_idx] {
@@ -686,16 +857,59 @@ impl PlayingField {
                     break;
                 }
 
-                min_x = min_x.min(x);
-                min_y = min_y.min(y);
-                max_x = max_x.max(x);
-                max_y = max_y.max(y);
+                arms[i] += 1;
             }
         }
+        //a row/column covered completely is seen by both arms
+        arms[0] = arms[0].min(self.width as i32 - 1 - arms[1]);
+        arms[2] = arms[2].min(self.height as i32 - 1 - arms[3]);
 
+        //on wrap-around boards the bounds may reach beyond the board edges
         let tower = &mut self.towers[tower_index as usize];
-        tower.bounds = Rect::new(Vector2D::new(min_x, min_y), Vector2D::new(max_x-min_x+1, max_y-min_y+1));
+        tower.bounds = Rect::new(Vector2D::new(tower_xy.0 - arms[1], tower_xy.1 - arms[3]), Vector2D::new(arms[0]+arms[1]+1, arms[2]+arms[3]+1));
 
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn playing_field_wrapped_round_trip(_gba : &mut agb::Gba) {
+        //tower 0 spreads over the left edge to the 2 tiles on the right
+        let mut pf = PlayingField::new(5, 2, None);
+        pf.wrap_around = true;
+        let field = [0, 1, 1, 0, 0, 2, 2, 2, 2, 3];
+        let towers = [(0, 0, 2), (1, 0, 1), (3, 1, 3), (4, 1, 0)];
+        for (x, y, height) in towers {
+            pf.towers.push(Tower { x, y, height, flattened_height : 0, bounds : Rect::new(Vector2D::new(x as i32, y as i32), Vector2D::new(1i32, 1i32)), clue : Clue::None });
+        }
+        for xy in 0..pf.area {
+            pf.field[xy] = field[xy];
+            pf.field_solution[xy] = field[xy];
+        }
+        for tower_index in 0..pf.towers.len() {
+            let tower = &pf.towers[tower_index];
+            pf.recalc_tower_bounds((tower.x as i32, tower.y as i32));
+        }
+        assert_eq!(pf.towers[0].bounds.position.x, -2);
+
+        let data = pf.save_as_u8_vec();
+        let mut loaded = PlayingField::new(1, 1, None);
+        assert_eq!(loaded.load_from_u8_vec(&data), data.len());
+
+        assert_eq!((loaded.width, loaded.height, loaded.area), (pf.width, pf.height, pf.area));
+        assert!(loaded.wrap_around);
+        assert!(loaded.spread_rule==pf.spread_rule);
+        assert_eq!(loaded.field[..loaded.area], pf.field[..pf.area]);
+        assert_eq!(loaded.field_solution[..loaded.area], pf.field_solution[..pf.area]);
+        assert_eq!(loaded.towers.len(), pf.towers.len());
+        for (lt, t) in loaded.towers.iter().zip(pf.towers.iter()) {
+            assert_eq!((lt.x, lt.y, lt.height, lt.flattened_height), (t.x, t.y, t.height, t.flattened_height));
+            assert_eq!(lt.bounds, t.bounds);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+
+use agb::rng::RandomNumberGenerator;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+const SINKS_PERCENT : usize = 3; //of the board area
+
+
+impl PlayingField {
+
+    //count of arms which end right in front of the sink at sink_xy, pointing onto it
+    fn calc_sink_spreads(&self, field : &[i32], sink_xy : (i32, i32)) -> i32 {
+        let mut count = 0;
+        for dir in DIRS {
+            let Some(xy) = self.step_xy(sink_xy, dir) else {
+                continue;
+            };
+            let tower_index = field[lin2d!(xy.0 as usize, xy.1 as usize, self.width)];
+            if tower_index==NO_TOWER {
+                continue;
+            }
+
+            //the arm of the tower towards the sink has to end at xy
+            let tower = &self.towers[tower_index as usize];
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            let arm_dir = (-dir.0, -dir.1);
+            let mut end_xy = tower_xy;
+            while let Some(next_xy) = self.step_xy(end_xy, arm_dir) {
+                if next_xy==tower_xy || field[lin2d!(next_xy.0 as usize, next_xy.1 as usize, self.width)]!=tower_index {
+                    break;
+                }
+                end_xy = next_xy;
+            }
+            if end_xy==xy && end_xy!=tower_xy {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    //spreads the sink at xy still has to absorb, negative => too many end in front of it
+    pub fn calc_sink_remaining(&self, xy : (i32, i32)) -> i32 {
+        match self.cells[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] {
+            Cell::Sink(count) => count - self.calc_sink_spreads(&self.field, xy),
+            _ => 0,
+        }
+    }
+
+    pub fn are_sinks_satisfied(&self) -> bool {
+        (0..self.area).all(|i| self.calc_sink_remaining(((i%self.width) as i32, (i/self.width) as i32))==0)
+    }
+
+    pub fn set_sink(&mut self, xy : (i32, i32), count : i32) {
+        self.cells[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] = Cell::Sink(count);
+    }
+
+    //replaces all sinks, has to happen before the towers get generated
+    //only straight spreads have arms which can end in front of a sink
+    pub fn place_random_sinks(&mut self, rng : &mut RandomNumberGenerator) {
+        for cell in self.cells.iter_mut() {
+            if matches!(*cell, Cell::Sink(_)) {
+                *cell = Cell::Open;
+            }
+        }
+        if self.spread_rule==SpreadRule::Bendy {
+            return;
+        }
+
+        let mut open_tiles : Vec<usize> = (0..self.area).filter(|i| self.is_open(*i)).collect();
+        let count = (self.area*SINKS_PERCENT/100).max(1).min(open_tiles.len().saturating_sub(1));
+        crate::util::rng::fisher_yates_shuffle_vec_inplace(&mut open_tiles, rng);
+        for lin_idx in open_tiles.into_iter().take(count) {
+            self.cells[lin_idx] = Cell::Sink(0);
+        }
+    }
+
+    //every sink absorbs as many spreads as end in front of it in the solution
+    //has to happen after the towers got generated
+    pub fn count_sink_spreads(&mut self) {
+        for lin_idx in 0..self.area {
+            if let Cell::Sink(_) = self.cells[lin_idx] {
+                let count = self.calc_sink_spreads(&self.field_solution, ((lin_idx%self.width) as i32, (lin_idx/self.width) as i32));
+                self.cells[lin_idx] = Cell::Sink(count);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,139 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::forced_moves::ForcedMove;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+
+impl PlayingField {
+
+    //depth first search for a layout which covers every free tile, starting from the current field
+    //only flattens, gives up after max_moves tried spreads, None => no layout found (in time)
+    //the field is left as it was
+    pub fn calc_solution(&mut self, max_moves : u32) -> Option<Vec<i32>> {
+        self.calc_solution_accepted(max_moves, |_| true)
+    }
+
+    //like calc_solution, but the generated solution does not count
+    pub(super) fn calc_other_solution(&mut self, max_moves : u32) -> Option<Vec<i32>> {
+        let solution = self.field_solution[..self.area].to_vec();
+        self.calc_solution_accepted(max_moves, |field| field!=&solution[..])
+    }
+
+    //the search goes on past layouts for which accept is false
+    fn calc_solution_accepted(&mut self, max_moves : u32, accept : impl Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        //spreads to choose from per search level and the count of them tried so far
+        let mut stack : Vec<(Vec<ForcedMove>, usize)> = Vec::new();
+        let solution = self.search_solution(&mut stack, max_moves, accept);
+        self.take_back_solver_moves(&stack);
+        solution
+    }
+
+    fn take_back_solver_moves(&mut self, stack : &[(Vec<ForcedMove>, usize)]) {
+        for (moves, next) in stack.iter().rev() {
+            if *next>0 {
+                self.set_solver_move(&moves[*next-1], false);
+            }
+        }
+    }
+
+    fn search_solution(&mut self, stack : &mut Vec<(Vec<ForcedMove>, usize)>, max_moves : u32, accept : impl Fn(&[i32]) -> bool) -> Option<Vec<i32>> {
+        let mut tried = 0;
+        loop {
+            match self.calc_solver_moves() {
+                None => {
+                    if self.is_solved() && accept(&self.field[..self.area]) {
+                        return Some(self.field[..self.area].to_vec());
+                    }
+                },
+                Some(moves) => stack.push((moves, 0)),
+            }
+
+            //take back the spread of the deepest level and try its next one, exhausted levels get dropped
+            loop {
+                let (moves, next) = stack.last_mut()?;
+                if *next<moves.len() && tried>=max_moves {
+                    return None;
+                }
+                if *next>0 {
+                    self.set_solver_move(&moves[*next-1], false);
+                }
+                if *next<moves.len() {
+                    tried += 1;
+                    self.set_solver_move(&moves[*next], true);
+                    *next += 1;
+                    break;
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    //like flatten/deflatten, but the notes and tower bounds stay untouched
+    fn set_solver_move(&mut self, mv : &ForcedMove, flatten : bool) {
+        let tower_index = self.field[lin2d!(mv.tower_xy.0 as usize, mv.tower_xy.1 as usize, self.width)];
+        let count = mv.xys.len() as i32;
+        self.towers[tower_index as usize].flattened_height += if flatten {-count} else {count};
+        for xy in mv.xys.iter() {
+            self.field[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] = if flatten {tower_index} else {NO_TOWER};
+        }
+    }
+
+    //spreads one of which has to be part of any layout completing the field, empty => dead end
+    //None => no free tile left
+    fn calc_solver_moves(&self) -> Option<Vec<ForcedMove>> {
+        match self.spread_rule {
+            SpreadRule::Straight => {
+                for tower in self.towers.iter() {
+                    let remaining = tower.flattened_height - 1;
+                    if remaining>0 && self.calc_free_space((tower.x as i32, tower.y as i32), remaining).iter().sum::<i32>()<remaining {
+                        return Some(Vec::new());
+                    }
+                }
+                //the first free tile has to be covered by one of the spreads reaching it
+                let lin_idx = (0..self.area).find(|i| self.is_free((i%self.width) as i32, (i/self.width) as i32))?;
+                Some(self.calc_spreads_reaching(((lin_idx%self.width) as i32, (lin_idx/self.width) as i32)))
+            },
+            SpreadRule::Bendy => {
+                (0..self.area).find(|i| self.is_free((i%self.width) as i32, (i/self.width) as i32))?;
+                if !self.calc_bendy_dead_end_xys().is_empty() {
+                    return Some(Vec::new());
+                }
+                //the tower with the fewest free neighbours grows by one of them
+                let mut best : Option<Vec<ForcedMove>> = None;
+                for (tower_index, tower) in self.towers.iter().enumerate() {
+                    if tower.flattened_height<=1 {
+                        continue;
+                    }
+                    let tower_xy = (tower.x as i32, tower.y as i32);
+                    let mut moves : Vec<ForcedMove> = Vec::new();
+                    for y in 0..self.height as i32 {
+                        for x in 0..self.width as i32 {
+                            if self.field[lin2d!(x as usize, y as usize, self.width)]!=tower_index as i32 {
+                                continue;
+                            }
+                            for dir in DIRS {
+                                let Some(xy) = self.step_xy((x, y), dir) else {
+                                    continue;
+                                };
+                                if self.is_free(xy.0, xy.1) && !moves.iter().any(|m| m.xys[0]==xy) {
+                                    moves.push(ForcedMove { tower_xy, xys : vec![xy] });
+                                }
+                            }
+                        }
+                    }
+                    if best.as_ref().map_or(true, |b| moves.len()<b.len()) {
+                        best = Some(moves);
+                    }
+                }
+                Some(best.unwrap_or_default())
+            },
+        }
+    }
+}
\ No newline at end of file
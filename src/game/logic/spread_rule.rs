@@ -0,0 +1,294 @@
+extern crate alloc;
+
+use super::*;
+
+use alloc::vec::Vec;
+use alloc::vec;
+
+use agb::fixnum::{Rect, Vector2D};
+use agb::rng::RandomNumberGenerator;
+
+
+const DIRS : [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+const MAX_BENDY_TOWER_HEIGHT : i32 = 12;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SpreadRule {
+    Straight, //towers spread along straight lines from their origin
+    Bendy, //the spread may be any connected shape containing the origin
+}
+
+impl SpreadRule {
+
+    pub fn from_u8(v : u8) -> SpreadRule {
+        match v {
+            1 => SpreadRule::Bendy,
+            _ => SpreadRule::Straight,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            SpreadRule::Straight => 0,
+            SpreadRule::Bendy => 1,
+        }
+    }
+}
+
+impl PlayingField {
+
+    //true if every tower in field covers a shape allowed by the spread rule
+    pub fn is_valid_layout(&self, field : &[i32]) -> bool {
+        for (tower_index, tower) in self.towers.iter().enumerate() {
+            let tower_index = tower_index as i32;
+            let tower_xy = (tower.x as i32, tower.y as i32);
+            if field[lin2d!(tower.x, tower.y, self.width)]!=tower_index {
+                return false;
+            }
+
+            let count = field[..self.area].iter().filter(|t| **t==tower_index).count();
+            let connected = match self.spread_rule {
+                SpreadRule::Straight => {
+                    //the tiles of every arm have to follow each other without a gap
+                    let mut arms = 1;
+                    for dir in DIRS {
+                        let mut xy = tower_xy;
+                        while let Some(next_xy) = self.step_xy(xy, dir) {
+                            if next_xy==tower_xy || field[lin2d!(next_xy.0 as usize, next_xy.1 as usize, self.width)]!=tower_index {
+                                break;
+                            }
+                            arms += 1;
+                            xy = next_xy;
+                        }
+                    }
+                    //a row/column covered completely is seen by both of its arms
+                    arms.min(count)
+                },
+                SpreadRule::Bendy => self.calc_connected_xys(&[tower_xy], |x, y| field[lin2d!(x as usize, y as usize, self.width)]==tower_index).len(),
+            };
+            if connected!=count {
+                return false;
+            }
+        }
+        true
+    }
+
+    //tiles connected to any of start_xys over tiles for which belongs is true
+    fn calc_connected_xys(&self, start_xys : &[(i32, i32)], belongs : impl Fn(i32, i32) -> bool) -> Vec<(i32, i32)> {
+        let mut visited = vec![false; self.area];
+        let mut xys = Vec::new();
+        let mut stack = start_xys.to_vec();
+        for xy in start_xys.iter() {
+            visited[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] = true;
+        }
+        while let Some(xy) = stack.pop() {
+            xys.push(xy);
+            for dir in DIRS {
+                let Some((x, y)) = self.step_xy(xy, dir) else {
+                    continue;
+                };
+                let lin_idx = lin2d!(x as usize, y as usize, self.width);
+                if !visited[lin_idx] && belongs(x, y) {
+                    visited[lin_idx] = true;
+                    stack.push((x, y));
+                }
+            }
+        }
+        xys
+    }
+
+    //bendy rule: the free tile next to from_xy in dir, if the tower covering from_xy may spread onto it
+    pub fn calc_bendy_flatten_xys(&self, tower_xy : (i32, i32), from_xy : (i32, i32), dir : (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        let tower_index = self.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, self.width)];
+        if tower_index==NO_TOWER || self.towers[tower_index as usize].flattened_height<=1 {
+            return None;
+        }
+        if self.field[lin2d!(from_xy.0 as usize, from_xy.1 as usize, self.width)]!=tower_index {
+            return None;
+        }
+        let xy = self.step_xy(from_xy, dir)?;
+        if self.is_free(xy.0, xy.1) {Some(vec![xy])} else {None}
+    }
+
+    //bendy rule: from_xy itself, if it can be taken back while stepping onto the tower tile in dir
+    //the origin and locked tiles are never taken back, every other tile must stay connected to one of them
+    pub fn calc_bendy_deflatten_xys(&self, tower_xy : (i32, i32), from_xy : (i32, i32), dir : (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        let tower_index = self.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, self.width)];
+        if tower_index==NO_TOWER || from_xy==tower_xy {
+            return None;
+        }
+        let xy = self.step_xy(from_xy, dir)?;
+        if self.field[lin2d!(xy.0 as usize, xy.1 as usize, self.width)]!=tower_index {
+            return None;
+        }
+        let from_lin_idx = lin2d!(from_xy.0 as usize, from_xy.1 as usize, self.width);
+        if self.field[from_lin_idx]!=tower_index || self.cells[from_lin_idx]==Cell::Locked {
+            return None;
+        }
+
+        let mut field = self.field;
+        field[from_lin_idx] = NO_TOWER;
+        let count = field[..self.area].iter().filter(|t| **t==tower_index).count();
+        let mut anchor_xys = vec![tower_xy];
+        for lin_idx in 0..self.area {
+            if self.cells[lin_idx]==Cell::Locked && field[lin_idx]==tower_index {
+                anchor_xys.push(((lin_idx%self.width) as i32, (lin_idx/self.width) as i32));
+            }
+        }
+        let connected = self.calc_connected_xys(&anchor_xys, |x, y| field[lin2d!(x as usize, y as usize, self.width)]==tower_index).len();
+        if connected==count {Some(vec![from_xy])} else {None}
+    }
+
+    //bounding box of all tiles of the tower, the straight rule only needs to look along the arms
+    pub(super) fn calc_bendy_tower_bounds(&self, tower_index : i32) -> (i32, i32, i32, i32) {
+        let tower = &self.towers[tower_index as usize];
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (tower.x as i32, tower.y as i32, tower.x as i32, tower.y as i32);
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.field[lin2d!(x as usize, y as usize, self.width)]==tower_index {
+                    min_x = min_x.min(x);
+                    min_y = min_y.min(y);
+                    max_x = max_x.max(x);
+                    max_y = max_y.max(y);
+                }
+            }
+        }
+        (min_x, min_y, max_x, max_y)
+    }
+
+    //connected areas of free tiles: area index per tile (-1 => not free) and the size of every area
+    pub(super) fn calc_free_areas(&self) -> (Vec<i32>, Vec<i32>) {
+        let mut area_of = vec![-1; self.area];
+        let mut sizes = Vec::new();
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if !self.is_free(x, y) || area_of[lin2d!(x as usize, y as usize, self.width)]!=-1 {
+                    continue;
+                }
+                let xys = self.calc_connected_xys(&[(x, y)], |x, y| self.is_free(x, y));
+                for xy in xys.iter() {
+                    area_of[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] = sizes.len() as i32;
+                }
+                sizes.push(xys.len() as i32);
+            }
+        }
+        (area_of, sizes)
+    }
+
+    //free areas bordering the tower with tower_index
+    pub(super) fn calc_areas_touched(&self, area_of : &[i32], tower_index : i32) -> Vec<i32> {
+        let mut areas = Vec::new();
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.field[lin2d!(x as usize, y as usize, self.width)]!=tower_index {
+                    continue;
+                }
+                for dir in DIRS {
+                    if let Some((nx, ny)) = self.step_xy((x, y), dir) {
+                        let area = area_of[lin2d!(nx as usize, ny as usize, self.width)];
+                        if area!=-1 && !areas.contains(&area) {
+                            areas.push(area);
+                        }
+                    }
+                }
+            }
+        }
+        areas
+    }
+
+    //bendy rule: towers with height left which border the free area of xy
+    pub(super) fn calc_bendy_towers_reaching(&self, xy : (i32, i32)) -> Vec<usize> {
+        let (area_of, _) = self.calc_free_areas();
+        let area = area_of[lin2d!(xy.0 as usize, xy.1 as usize, self.width)];
+        (0..self.towers.len())
+            .filter(|t| self.towers[*t].flattened_height>1 && self.calc_areas_touched(&area_of, *t as i32).contains(&area))
+            .collect()
+    }
+
+    //bendy rule: the tiles of areas the bordering towers can not fill up and origins of towers without enough room
+    pub(super) fn calc_bendy_dead_end_xys(&self) -> Vec<(i32, i32)> {
+        let (area_of, sizes) = self.calc_free_areas();
+        let mut area_capacity = vec![0; sizes.len()];
+        let mut xys = Vec::new();
+
+        for (tower_index, tower) in self.towers.iter().enumerate() {
+            let remaining = tower.flattened_height - 1;
+            if remaining<=0 {
+                continue;
+            }
+            let areas = self.calc_areas_touched(&area_of, tower_index as i32);
+            for area in areas.iter() {
+                area_capacity[*area as usize] += remaining;
+            }
+            if areas.iter().map(|a| sizes[*a as usize]).sum::<i32>()<remaining {
+                xys.push((tower.x as i32, tower.y as i32));
+            }
+        }
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let area = area_of[lin2d!(x as usize, y as usize, self.width)];
+                if area!=-1 && area_capacity[area as usize]<sizes[area as usize] {
+                    xys.push((x, y));
+                }
+            }
+        }
+
+        xys
+    }
+
+    //bendy rule: every tower grows from a random origin tile by tile onto random free neighbours
+    pub(super) fn init_with_random_bendy_towers(&mut self, rng : &mut RandomNumberGenerator) {
+        let mut new_tower_index = 0;
+        loop {
+            let empty_tiles : Vec<usize> = (0..self.area)
+                .filter(|i| self.field_solution[*i]==NO_TOWER && self.is_open(*i))
+                .collect();
+            if empty_tiles.is_empty() {
+                break;
+            }
+
+            let new_tower_lin_xy = empty_tiles[(rng.gen().abs() as usize)%empty_tiles.len()];
+            let new_tower_x = new_tower_lin_xy % self.width;
+            let new_tower_y = new_tower_lin_xy / self.width;
+            let target_height = 1 + rng.gen().abs()%MAX_BENDY_TOWER_HEIGHT;
+
+            self.field_solution[new_tower_lin_xy] = new_tower_index;
+            let mut tower_xys = vec![(new_tower_x as i32, new_tower_y as i32)];
+
+            while (tower_xys.len() as i32)<target_height {
+                let mut candidates = Vec::new();
+                for xy in tower_xys.iter() {
+                    for dir in DIRS {
+                        let Some((x, y)) = self.step_xy(*xy, dir) else {
+                            continue;
+                        };
+                        let lin_xy = lin2d!(x as usize, y as usize, self.width);
+                        if self.field_solution[lin_xy]==NO_TOWER && self.is_open(lin_xy) && !candidates.contains(&(x, y)) {
+                            candidates.push((x, y));
+                        }
+                    }
+                }
+                if candidates.is_empty() {
+                    break;
+                }
+                let xy = candidates[(rng.gen().abs() as usize)%candidates.len()];
+                self.field_solution[lin2d!(xy.0 as usize, xy.1 as usize, self.width)] = new_tower_index;
+                tower_xys.push(xy);
+            }
+
+            let new_tower_height = tower_xys.len() as i32;
+            self.towers.push(Tower {
+                x: new_tower_x,
+                y: new_tower_y,
+                height: new_tower_height,
+                flattened_height: new_tower_height,
+                bounds: Rect::new(Vector2D::new(new_tower_x as i32, new_tower_y as i32), Vector2D::new(1i32, 1i32)),
+                clue: Clue::None,
+            });
+            new_tower_index += 1;
+        }
+    }
+}
\ No newline at end of file
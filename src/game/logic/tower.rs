@@ -1,9 +1,12 @@
 use agb::fixnum::Rect;
 
+use super::Clue;
+
 pub struct Tower {
     pub x: usize,
     pub y: usize,
     pub height: i32,
     pub flattened_height: i32,
     pub bounds: Rect<i32>,
+    pub clue: Clue,
 }
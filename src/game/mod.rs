@@ -1,5 +1,13 @@
 pub mod logic;
 pub mod view;
+mod campaign;
+mod controls;
+mod replay;
+mod session;
 mod settings;
 
+pub use campaign::*;
+pub use controls::*;
+pub use replay::*;
+pub use session::*;
 pub use settings::*;
\ No newline at end of file
@@ -0,0 +1,227 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use super::logic::{PlayingField, SpreadRule};
+use super::view::PlayingFieldView;
+
+
+const REPLAY_HEADER_LEN : usize = 9 + 16 + 2; //board setup, truncated flag, seed, count of moves
+const REPLAY_MOVE_LEN : usize = 6; //without the tiles, 2 bytes each
+
+//the replay is kept in the board save and once more for the last completed board, both have to fit into the sram
+const MAX_REPLAY_MOVES : usize = 2000;
+const MAX_REPLAY_DATA_LEN : usize = 12*1024; //room for MAX_REPLAY_MOVES moves without tiles, longer moves hit this limit first
+const _ : () = assert!(MAX_REPLAY_MOVES<=u16::MAX as usize); //the count of moves is saved in 2 bytes
+
+
+//one flatten or deflatten as it was applied to the board
+pub struct ReplayMove {
+    pub frame : u32, //since the board was started
+    pub tower_xy : (i32, i32),
+    pub xys : Vec<(i32, i32)>,
+    pub flatten : bool,
+}
+
+//setup of a random board and the moves done on it, the board itself is generated again from the seed
+pub struct Replay {
+    pub width : usize,
+    pub height : usize,
+    pub spread_rule : SpreadRule,
+    pub wrap_around : bool,
+    pub clue_towers : bool,
+    pub sinks : bool,
+    pub rocks_level : u8,
+    pub locked_level : u8,
+    pub seed : [u32; 4],
+    pub moves : Vec<ReplayMove>,
+    pub truncated : bool, //moves after the size limit got dropped
+}
+
+impl Replay {
+
+    //pf has to be of the size of the replay
+    pub fn init_board(&self, pf : &mut PlayingField, pfv : &mut PlayingFieldView) {
+        pf.spread_rule = self.spread_rule;
+        pf.wrap_around = self.wrap_around;
+        pfv.init_with_random_towers(pf, Some(self.seed), self.rocks_level, self.locked_level, self.clue_towers, self.sinks);
+    }
+
+    //false => the replay is full, no more moves get recorded and it is marked as truncated
+    pub fn push_move(&mut self, mv : ReplayMove) -> bool {
+        if self.truncated || self.moves.len()>=MAX_REPLAY_MOVES || self.calc_data_len() + REPLAY_MOVE_LEN + 2*mv.xys.len()>MAX_REPLAY_DATA_LEN {
+            self.truncated = true;
+            return false;
+        }
+        self.moves.push(mv);
+        true
+    }
+
+    //bytes written by save_as_u8_vec
+    fn calc_data_len(&self) -> usize {
+        REPLAY_HEADER_LEN + self.moves.iter().map(|mv| REPLAY_MOVE_LEN + 2*mv.xys.len()).sum::<usize>()
+    }
+
+    //frame of the last move, 0 => no moves yet
+    pub fn last_frame(&self) -> u32 {
+        self.moves.last().map_or(0, |mv| mv.frame)
+    }
+
+    //moves are stored with the frames passed since the move before (at most u16::MAX)
+    pub fn save_as_u8_vec(&self) -> Vec<u8> {
+        let mut data = Vec::<u8>::new();
+        data.push(self.width as u8);
+        data.push(self.height as u8);
+        data.push(self.spread_rule.as_u8());
+        data.push(if self.wrap_around {1} else {0});
+        data.push(if self.clue_towers {1} else {0});
+        data.push(if self.sinks {1} else {0});
+        data.push(self.rocks_level);
+        data.push(self.locked_level);
+        data.push(if self.truncated {1} else {0});
+        for seed_part in self.seed {
+            data.extend_from_slice(&seed_part.to_be_bytes());
+        }
+        assert!(self.moves.len()<=u16::MAX as usize);
+        data.push((self.moves.len()>>8) as u8);
+        data.push(self.moves.len() as u8);
+
+        let mut last_frame = 0;
+        for mv in self.moves.iter() {
+            let delta = mv.frame.saturating_sub(last_frame).min(u16::MAX as u32);
+            last_frame = mv.frame;
+            data.push((delta>>8) as u8);
+            data.push(delta as u8);
+            data.push(mv.tower_xy.0 as u8);
+            data.push(mv.tower_xy.1 as u8);
+            data.push(if mv.flatten {1} else {0});
+            data.push(mv.xys.len() as u8);
+            for xy in mv.xys.iter() {
+                data.push(xy.0 as u8);
+                data.push(xy.1 as u8);
+            }
+        }
+        data
+    }
+
+    //returns the replay and the count of bytes read
+    pub fn load_from_u8_slice(data : &[u8]) -> (Self, usize) {
+        let mut seed = [0; 4];
+        for (i, seed_part) in seed.iter_mut().enumerate() {
+            *seed_part = u32::from_be_bytes([data[9+i*4], data[10+i*4], data[11+i*4], data[12+i*4]]);
+        }
+        let count_moves = (data[25] as usize)<<8 | data[26] as usize;
+
+        let mut di = REPLAY_HEADER_LEN;
+        let mut frame = 0;
+        let mut moves = Vec::with_capacity(count_moves);
+        for _ in 0..count_moves {
+            frame += (data[di] as u32)<<8 | data[di+1] as u32;
+            let count_xys = data[di+5] as usize;
+            moves.push(ReplayMove {
+                frame,
+                tower_xy : (data[di+2] as i32, data[di+3] as i32),
+                flatten : data[di+4]!=0,
+                xys : (0..count_xys).map(|i| (data[di+6+i*2] as i32, data[di+7+i*2] as i32)).collect(),
+            });
+            di += REPLAY_MOVE_LEN + count_xys*2;
+        }
+
+        let replay = Replay {
+            width : data[0] as usize,
+            height : data[1] as usize,
+            spread_rule : SpreadRule::from_u8(data[2]),
+            wrap_around : data[3]!=0,
+            clue_towers : data[4]!=0,
+            sinks : data[5]!=0,
+            rocks_level : data[6],
+            locked_level : data[7],
+            seed,
+            moves,
+            truncated : data[8]!=0,
+        };
+        (replay, di)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_replay() -> Replay {
+        Replay {
+            width : 7,
+            height : 5,
+            spread_rule : SpreadRule::Straight,
+            wrap_around : true,
+            clue_towers : false,
+            sinks : true,
+            rocks_level : 2,
+            locked_level : 1,
+            seed : [0x12345678, 0x9abcdef0, 1, u32::MAX],
+            moves : Vec::new(),
+            truncated : false,
+        }
+    }
+
+    #[test_case]
+    fn replay_round_trip(_gba : &mut agb::Gba) {
+        let mut replay = test_replay();
+        assert!(replay.push_move(ReplayMove { frame : 30, tower_xy : (1, 2), xys : alloc::vec![(1, 3), (1, 4)], flatten : true }));
+        assert!(replay.push_move(ReplayMove { frame : 300, tower_xy : (6, 4), xys : Vec::new(), flatten : false }));
+        assert!(replay.push_move(ReplayMove { frame : 301, tower_xy : (0, 0), xys : alloc::vec![(6, 0)], flatten : true }));
+
+        let data = replay.save_as_u8_vec();
+        assert_eq!(data.len(), replay.calc_data_len());
+        let (loaded, len) = Replay::load_from_u8_slice(&data);
+        assert_eq!(len, data.len());
+
+        assert_eq!((loaded.width, loaded.height), (replay.width, replay.height));
+        assert!(loaded.spread_rule==replay.spread_rule);
+        assert_eq!((loaded.wrap_around, loaded.clue_towers, loaded.sinks), (replay.wrap_around, replay.clue_towers, replay.sinks));
+        assert_eq!((loaded.rocks_level, loaded.locked_level), (replay.rocks_level, replay.locked_level));
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!(loaded.truncated, replay.truncated);
+        assert_eq!(loaded.moves.len(), replay.moves.len());
+        for (lmv, mv) in loaded.moves.iter().zip(replay.moves.iter()) {
+            assert_eq!(lmv.frame, mv.frame);
+            assert_eq!(lmv.tower_xy, mv.tower_xy);
+            assert_eq!(lmv.xys, mv.xys);
+            assert_eq!(lmv.flatten, mv.flatten);
+        }
+    }
+
+    //fills a replay until it refuses a move and checks the truncated replay survives saving
+    fn capped_round_trip(count_xys : usize) -> Replay {
+        let mut replay = test_replay();
+        let mut frame = 0;
+        while replay.push_move(ReplayMove { frame, tower_xy : (3, 2), xys : alloc::vec![(3, 3); count_xys], flatten : frame%2==0 }) {
+            frame += 1;
+        }
+        assert!(replay.truncated);
+        assert!(!replay.push_move(ReplayMove { frame, tower_xy : (3, 2), xys : Vec::new(), flatten : true }));
+
+        let data = replay.save_as_u8_vec();
+        assert!(data.len()<=MAX_REPLAY_DATA_LEN);
+        let (loaded, len) = Replay::load_from_u8_slice(&data);
+        assert_eq!(len, data.len());
+        assert!(loaded.truncated);
+        assert_eq!(loaded.moves.len(), replay.moves.len());
+        assert_eq!(loaded.last_frame(), replay.last_frame());
+        replay
+    }
+
+    #[test_case]
+    fn replay_data_capped_round_trip(_gba : &mut agb::Gba) {
+        let replay = capped_round_trip(1);
+        assert!(replay.moves.len()<MAX_REPLAY_MOVES);
+    }
+
+    #[test_case]
+    fn replay_moves_capped_round_trip(_gba : &mut agb::Gba) {
+        //moves without tiles only hit the count limit, which needs both bytes of the saved count
+        let replay = capped_round_trip(0);
+        assert_eq!(replay.moves.len(), MAX_REPLAY_MOVES);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+
+use super::logic::{PlayingField, MAX_PLAYING_FIELD_HEIGHT, MAX_PLAYING_FIELD_WIDTH};
+use super::{CampaignLevel, Settings, CAMPAIGN_LEVELS_PER_CHAPTER, CAMPAIGN_MAX_STARS};
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SessionMode {
+    Single, //one board without pressure
+    TimeAttack, //as many boards of one size as possible within a time limit
+    ZenEndless, //boards of slowly increasing size, no timer
+    Campaign, //the selected campaign level, rated with stars
+}
+
+pub const TIME_ATTACK_MINUTES : [u32; 2] = [5, 10];
+const FRAMES_PER_SECOND : u32 = 60;
+const ADVANCE_DELAY_FRAMES : u32 = 60; //the solved board stays visible for a moment
+const ZEN_ENDLESS_START_SIZE : usize = 5;
+
+pub const HIGH_SCORES_COUNT : usize = 5; //entries per table
+pub const HIGH_SCORE_TABLES_COUNT : usize = TIME_ATTACK_MINUTES.len() + 1; //time attack per time limit, zen endless last
+pub const HIGH_SCORE_TABLE_NAMES : [&str; HIGH_SCORE_TABLES_COUNT] = ["TIME ATTACK 5 MIN", "TIME ATTACK 10 MIN", "ZEN ENDLESS"];
+pub const HIGH_SCORES_DATA_LEN : usize = HIGH_SCORE_TABLES_COUNT*HIGH_SCORES_COUNT*6; //bytes in the save data
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub struct HighScore {
+    pub boards : u16, //boards solved
+    pub towers : u16, //towers of the solved boards
+    pub width : u8, //board size, the biggest one reached in zen endless
+    pub height : u8,
+}
+
+pub struct HighScores {
+    tables : [[HighScore; HIGH_SCORES_COUNT]; HIGH_SCORE_TABLES_COUNT], //best first, empty entries have 0 boards
+}
+
+impl HighScores {
+
+    pub fn new() -> Self {
+        HighScores {
+            tables : [[HighScore { boards : 0, towers : 0, width : 0, height : 0 }; HIGH_SCORES_COUNT]; HIGH_SCORE_TABLES_COUNT],
+        }
+    }
+
+    pub fn table(&self, table : usize) -> &[HighScore] {
+        &self.tables[table]
+    }
+
+    //rank the score got in the table, None => not good enough (or no board solved)
+    pub fn insert(&mut self, table : usize, score : HighScore) -> Option<usize> {
+        if score.boards==0 {
+            return None;
+        }
+        let entries = &mut self.tables[table];
+        let rank = entries.iter().position(|e| (score.boards, score.towers)>(e.boards, e.towers))?;
+        entries[rank..].rotate_right(1);
+        entries[rank] = score;
+        Some(rank)
+    }
+
+    pub fn save_as_u8_arr(&self) -> [u8; HIGH_SCORES_DATA_LEN] {
+        let mut data = [0; HIGH_SCORES_DATA_LEN];
+        for (i, e) in self.tables.iter().flatten().enumerate() {
+            data[i*6..i*6+6].copy_from_slice(&[(e.boards>>8) as u8, e.boards as u8, (e.towers>>8) as u8, e.towers as u8, e.width, e.height]);
+        }
+        data
+    }
+
+    pub fn load_from_u8_slice(&mut self, data : &[u8]) {
+        for (i, e) in self.tables.iter_mut().flatten().enumerate() {
+            let d = &data[i*6..i*6+6];
+            *e = HighScore {
+                boards : (d[0] as u16)<<8 | d[1] as u16,
+                towers : (d[2] as u16)<<8 | d[3] as u16,
+                width : d[4],
+                height : d[5],
+            };
+        }
+    }
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SessionEvent {
+    None,
+    NextBoard, //the solved board gets replaced by a new one of size board_size()
+    TimeUp,
+    LevelCompleted, //the campaign level got solved, see stars()
+}
+
+//state of a running time attack or zen endless session
+pub struct Session {
+    pub mode : SessionMode,
+    minutes_index : usize,
+    width : usize,
+    height : usize,
+    frames_left : u32,
+    solved_frames : u32, //frames since the current board got solved
+    pub score : HighScore, //running totals
+
+    campaign_level : usize,
+    frames_played : u32,
+    moves : u32,
+    stars : Option<u8>, //rating of the solved campaign level
+}
+
+impl Session {
+
+    pub fn new(mode : SessionMode, settings : &Settings) -> Self {
+        let minutes_index = settings.time_attack_minutes_index as usize;
+        let campaign_level = settings.campaign.level as usize;
+        let (width, height) = match mode {
+            SessionMode::ZenEndless => (ZEN_ENDLESS_START_SIZE, ZEN_ENDLESS_START_SIZE),
+            SessionMode::Campaign => {
+                let level = CampaignLevel::new(campaign_level);
+                (level.width, level.height)
+            },
+            _ => (settings.playing_field_width, settings.playing_field_height),
+        };
+        Session {
+            mode,
+            minutes_index,
+            width,
+            height,
+            frames_left : TIME_ATTACK_MINUTES[minutes_index]*60*FRAMES_PER_SECOND,
+            solved_frames : 0,
+            score : HighScore { boards : 0, towers : 0, width : width as u8, height : height as u8 },
+
+            campaign_level,
+            frames_played : 0,
+            moves : 0,
+            stars : None,
+        }
+    }
+
+    pub fn board_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    //None => not a campaign session
+    pub fn campaign_level(&self) -> Option<usize> {
+        if self.mode==SessionMode::Campaign {Some(self.campaign_level)} else {None}
+    }
+
+    pub fn stars(&self) -> Option<u8> {
+        self.stars
+    }
+
+    //high score table of the mode, None => single boards and campaign levels are not ranked
+    pub fn high_score_table(&self) -> Option<usize> {
+        match self.mode {
+            SessionMode::Single | SessionMode::Campaign => None,
+            SessionMode::TimeAttack => Some(self.minutes_index),
+            SessionMode::ZenEndless => Some(TIME_ATTACK_MINUTES.len()),
+        }
+    }
+
+    //to be called once per frame with the current board and the count of moves done on it
+    pub fn frame(&mut self, pf : &PlayingField, moves : u32) -> SessionEvent {
+        if self.mode==SessionMode::Single {
+            return SessionEvent::None;
+        }
+
+        if self.mode==SessionMode::Campaign {
+            //the clock stops with the first solve, the rating stays
+            if self.stars.is_some() {
+                return SessionEvent::None;
+            }
+            self.frames_played = self.frames_played.saturating_add(1);
+            self.moves = moves;
+            if !pf.is_solved() {
+                return SessionEvent::None;
+            }
+            self.stars = Some(CampaignLevel::new(self.campaign_level).calc_stars(pf, self.frames_played, moves));
+            return SessionEvent::LevelCompleted;
+        }
+
+        if self.mode==SessionMode::TimeAttack {
+            self.frames_left = self.frames_left.saturating_sub(1);
+            if self.frames_left==0 {
+                return SessionEvent::TimeUp;
+            }
+        }
+
+        if !pf.is_solved() {
+            self.solved_frames = 0;
+            return SessionEvent::None;
+        };
+        self.solved_frames += 1;
+        if self.solved_frames<ADVANCE_DELAY_FRAMES {
+            return SessionEvent::None;
+        }
+        self.solved_frames = 0;
+
+        self.score.boards = self.score.boards.saturating_add(1);
+        self.score.towers = self.score.towers.saturating_add(pf.towers.len() as u16);
+        if self.mode==SessionMode::ZenEndless {
+            //every second board one side grows, width and height take turns
+            let step = self.score.boards as usize/2;
+            self.width = (ZEN_ENDLESS_START_SIZE + (step+1)/2).min(MAX_PLAYING_FIELD_WIDTH);
+            self.height = (ZEN_ENDLESS_START_SIZE + step/2).min(MAX_PLAYING_FIELD_HEIGHT);
+            self.score.width = self.width as u8;
+            self.score.height = self.height as u8;
+        }
+        SessionEvent::NextBoard
+    }
+
+    //line shown above the board, None => single board
+    pub fn status_text(&self) -> Option<String> {
+        match self.mode {
+            SessionMode::Single => None,
+            SessionMode::TimeAttack => {
+                let seconds = (self.frames_left + FRAMES_PER_SECOND - 1)/FRAMES_PER_SECOND;
+                Some(format!("SOLVED {}  {}:{:02}", self.score.boards, seconds/60, seconds%60))
+            },
+            SessionMode::ZenEndless => Some(format!("BOARD {}  TOWERS {}", self.score.boards + 1, self.score.towers)),
+            SessionMode::Campaign => {
+                let name = format!("{}-{}", self.campaign_level/CAMPAIGN_LEVELS_PER_CHAPTER + 1, self.campaign_level%CAMPAIGN_LEVELS_PER_CHAPTER + 1);
+                if let Some(stars) = self.stars {
+                    let rating : String = (0..CAMPAIGN_MAX_STARS).map(|s| if s<stars {'*'} else {'-'}).collect();
+                    Some(format!("{} SOLVED {}", name, rating))
+                } else {
+                    let seconds = (self.frames_played/FRAMES_PER_SECOND).min(99*60 + 59);
+                    Some(format!("{} MOVES {} {}:{:02}", name, self.moves, seconds/60, seconds%60))
+                }
+            },
+        }
+    }
+}
\ No newline at end of file
@@ -5,17 +5,52 @@ use alloc::vec::Vec;
 
 use crate::util::gbaex::ButtonControllerAutoRepeat;
 
-const SETTINGS_HEADER : [u8; 8] = [0xe7, 0x2a, 0xf5, 0x0c, 0x1d, 0x1b, 0x09, 0x18 ];
+use super::logic::{SpreadRule, AI_SKILLS_COUNT, OBSTACLE_LEVELS_COUNT};
+use super::view::{ForcedMovesAssist, TowerPalette, TOWER_COLORS_COUNT};
+use super::{Controls, CONTROLS_DATA_LEN, HighScores, HIGH_SCORES_DATA_LEN, TIME_ATTACK_MINUTES, Campaign, CAMPAIGN_DATA_LEN};
+use crate::audio::{SoundTheme, MAX_VOLUME};
+
+const SETTINGS_HEADER : [u8; 8] = [0xe7, 0x2a, 0xf5, 0x0c, 0x1d, 0x1b, 0x09, 0x19 ];
+const SETTINGS_VERSION : u8 = 1; //raise on every change of the layout behind the header, older versions get migrated on load
+const SETTINGS_DATA_LEN : usize = SETTINGS_HEADER.len() + 1 + 36 + CONTROLS_DATA_LEN + HIGH_SCORES_DATA_LEN + CAMPAIGN_DATA_LEN;
+
+//first release, same header but ending with 0x18 and without version: sound on/off, board size, seed and the saved board
+const LEGACY_SETTINGS_HEADER_END : u8 = 0x18;
+const LEGACY_SETTINGS_DATA_LEN : usize = SETTINGS_HEADER.len() + 21;
+const SAVE_DATA_MAX_LEN : usize = 32*1024; //sram
+
+pub const DEFAULT_SEED : [u32; 4] = [1014776995, 476057059, 3301633994, 706340607];
 
 pub struct Settings {
-    pub sound : bool,
+    pub music_volume : u8, //0..=MAX_VOLUME
+    pub effects_volume : u8, //0..=MAX_VOLUME
+    pub sound_theme : SoundTheme,
+
+    pub tower_palette : TowerPalette,
+
+    pub controls : Controls,
+
+    pub forced_moves_assist : ForcedMovesAssist,
+    pub dead_end_warnings : bool,
 
     pub playing_field_width: usize,
     pub playing_field_height: usize,
+    pub spread_rule : SpreadRule,
+    pub wrap_around : bool,
+    pub clue_towers : bool,
+    pub sinks : bool,
+    pub rocks_level : u8, //0..OBSTACLE_LEVELS_COUNT
+    pub locked_level : u8, //0..OBSTACLE_LEVELS_COUNT
+
+    pub time_attack_minutes_index : u8, //index into TIME_ATTACK_MINUTES
+    pub high_scores : HighScores,
+    pub campaign : Campaign,
+    pub ai_skill : u8, //0..AI_SKILLS_COUNT
 
     pub playing_field_seed : [u32; 4],
 
     pub playing_field_data : Option<Vec<u8>>,
+    pub replay_data : Option<Vec<u8>>, //moves of the last completed board, see Replay
 
 }
 
@@ -23,33 +58,84 @@ impl Settings {
     pub fn new() -> Settings {
         Settings {
 
-            sound : true,
+            music_volume : MAX_VOLUME/2,
+            effects_volume : MAX_VOLUME,
+            sound_theme : SoundTheme::Extended,
+
+            tower_palette : TowerPalette::Classic,
+
+            controls : Controls::new(),
+
+            forced_moves_assist : ForcedMovesAssist::Off,
+            dead_end_warnings : true,
 
             playing_field_width: 10,
             playing_field_height: 10,
-            playing_field_seed: [1014776995, 476057059, 3301633994, 706340607],
+            spread_rule : SpreadRule::Straight,
+            wrap_around : false,
+            clue_towers : false,
+            sinks : false,
+            rocks_level : 0,
+            locked_level : 0,
+            time_attack_minutes_index : 0,
+            high_scores : HighScores::new(),
+            campaign : Campaign::new(),
+            ai_skill : (AI_SKILLS_COUNT - 1) as u8,
+
+            playing_field_seed: DEFAULT_SEED,
 
             playing_field_data : None,
+            replay_data : None,
         }
     }
 
     pub fn save(&self, save_access : &mut SaveData) -> bool{
-        let pfd_len = if let Some(ref pfd) = self.playing_field_data {
+        let data_len = SETTINGS_DATA_LEN;
+        let mut pfd_len = if let Some(ref pfd) = self.playing_field_data {
             pfd.len()
         } else {
             0
         };
-        let data_len = SETTINGS_HEADER.len() + 21;
+        let mut replay_len = self.replay_data.as_ref().map_or(0, |rd| rd.len());
+        //if it does not fit, the replay is dropped first, then the saved board, so the settings are kept at least
+        if data_len+pfd_len+replay_len>SAVE_DATA_MAX_LEN {
+            replay_len = 0;
+        }
+        if data_len+pfd_len>SAVE_DATA_MAX_LEN {
+            pfd_len = 0;
+        }
         let mut data = Vec::<u8>::with_capacity(data_len);
         //write header
         for hb in SETTINGS_HEADER {
             data.push(hb);
         }
+        data.push(SETTINGS_VERSION);
+
+        data.push(self.music_volume);
+        data.push(self.effects_volume);
+        data.push(self.sound_theme.as_u8());
+
+        data.push(self.tower_palette.as_u8());
 
-        data.push(if self.sound {1} else {0});
+        data.extend_from_slice(&self.controls.save_as_u8_arr());
+
+        data.push(self.forced_moves_assist.as_u8());
+        data.push(if self.dead_end_warnings {1} else {0});
 
         data.push(self.playing_field_width as u8);
         data.push(self.playing_field_height as u8);
+        data.push(self.spread_rule.as_u8());
+        data.push(if self.wrap_around {1} else {0});
+        data.push(if self.clue_towers {1} else {0});
+        data.push(if self.sinks {1} else {0});
+
+        data.push(self.time_attack_minutes_index);
+        data.extend_from_slice(&self.high_scores.save_as_u8_arr());
+        data.extend_from_slice(&self.campaign.save_as_u8_arr());
+        data.push(self.ai_skill);
+
+        data.push(self.rocks_level);
+        data.push(self.locked_level);
         for seed_part in self.playing_field_seed {
             data.push(((seed_part&0xff000000)>>24) as u8);
             data.push(((seed_part&0x00ff0000)>>16) as u8);
@@ -61,20 +147,27 @@ impl Settings {
         let pfd_len_low = (pfd_len&0xff) as u8;
         data.push(pfd_len_high);
         data.push(pfd_len_low);
+        data.push(((replay_len&0xff00)>>8) as u8);
+        data.push((replay_len&0xff) as u8);
 
         assert!(data.len()==data_len);
 
-        let pb = save_access.prepare_write(0..(data_len+pfd_len));
+        let pb = save_access.prepare_write(0..(data_len+pfd_len+replay_len));
         if pb.is_ok() {
             let mut pb = pb.unwrap();
             if pb.write(0, &data).is_err() {
                 return false;
             };
-            if let Some(ref pfd) = self.playing_field_data {
+            if let Some(pfd) = self.playing_field_data.as_ref().filter(|_| pfd_len>0) {
                 if pb.write(data.len(), pfd).is_err() {
                     return false;
                 }
             }
+            if let Some(rd) = self.replay_data.as_ref().filter(|_| replay_len>0) {
+                if pb.write(data.len() + pfd_len, rd).is_err() {
+                    return false;
+                }
+            }
         } else {
             return false;
         }
@@ -84,7 +177,7 @@ impl Settings {
     }
 
     pub fn load(&mut self, save_access : &mut SaveData) -> bool{
-        let data_len = SETTINGS_HEADER.len() + 21;
+        let data_len = SETTINGS_DATA_LEN;
         let mut data = Vec::<u8>::new();
         data.resize(data_len, 0);
         if save_access.read(0, &mut data).is_ok() {
@@ -92,20 +185,47 @@ impl Settings {
             let mut di = 0;
 
             //check for correct header (if not correct, save data is corrupt or no save data where ever saved)
-            for (i,hb) in SETTINGS_HEADER.iter().enumerate() {
+            let header_end = SETTINGS_HEADER.len() - 1;
+            for (i,hb) in SETTINGS_HEADER[..header_end].iter().enumerate() {
                 if data[i]!=*hb {
                     return false;
                 }
             }
-            di+=SETTINGS_HEADER.len();
+            if data[header_end]==LEGACY_SETTINGS_HEADER_END {
+                return self.load_legacy(save_access, &data);
+            }
+            if data[header_end]!=SETTINGS_HEADER[header_end] || data[SETTINGS_HEADER.len()]!=SETTINGS_VERSION {
+                return false;
+            }
+            di+=SETTINGS_HEADER.len() + 1;
 
-            self.sound = data[di]!=0; di+=1;
+            self.music_volume = data[di].min(MAX_VOLUME); di+=1;
+            self.effects_volume = data[di].min(MAX_VOLUME); di+=1;
+            self.sound_theme = SoundTheme::from_u8(data[di]); di+=1;
+
+            self.tower_palette = TowerPalette::from_u8(data[di]); di+=1;
+
+            self.controls.load_from_u8_slice(&data[di..di+CONTROLS_DATA_LEN]); di+=CONTROLS_DATA_LEN;
+
+            self.forced_moves_assist = ForcedMovesAssist::from_u8(data[di]); di+=1;
+            self.dead_end_warnings = data[di]!=0; di+=1;
 
             self.playing_field_width = data[di] as usize; di+=1;
             self.playing_field_height = data[di] as usize; di+=1;
+            self.spread_rule = SpreadRule::from_u8(data[di]); di+=1;
+            self.wrap_around = data[di]!=0; di+=1;
+            self.clue_towers = data[di]!=0; di+=1;
+            self.sinks = data[di]!=0; di+=1;
+
+            self.time_attack_minutes_index = data[di].min(TIME_ATTACK_MINUTES.len() as u8 - 1); di+=1;
+            self.high_scores.load_from_u8_slice(&data[di..di+HIGH_SCORES_DATA_LEN]); di+=HIGH_SCORES_DATA_LEN;
+            self.campaign.load_from_u8_slice(&data[di..di+CAMPAIGN_DATA_LEN]); di+=CAMPAIGN_DATA_LEN;
+            self.ai_skill = data[di].min(AI_SKILLS_COUNT as u8 - 1); di+=1;
+            self.rocks_level = data[di].min(OBSTACLE_LEVELS_COUNT as u8 - 1); di+=1;
+            self.locked_level = data[di].min(OBSTACLE_LEVELS_COUNT as u8 - 1); di+=1;
 
             for seed_part in self.playing_field_seed.iter_mut() {
-                *seed_part = (data[di+0] as u32)<<24 | (data[di+1] as u32)<<16 | (data[di] as u32)<<8 | (data[di] as u32);
+                *seed_part = (data[di+0] as u32)<<24 | (data[di+1] as u32)<<16 | (data[di+2] as u32)<<8 | (data[di+3] as u32);
                 di+=4;
             }
 
@@ -119,6 +239,47 @@ impl Settings {
                 }
             }
 
+            self.replay_data = None;
+            let replay_len = (data[di+2] as usize)<<8 | (data[di+3] as usize);
+            if replay_len>0 {
+                let mut rd = Vec::<u8>::new();
+                rd.resize(replay_len, 0);
+                if save_access.read(data_len + pfd_len, &mut rd).is_ok() {
+                    self.replay_data = Some(rd);
+                }
+            }
+
+        }
+        true
+    }
+
+    //save data of the first release, the other settings keep their defaults
+    fn load_legacy(&mut self, save_access : &mut SaveData, data : &[u8]) -> bool {
+        let mut di = SETTINGS_HEADER.len();
+
+        if data[di]==0 {
+            self.music_volume = 0;
+            self.effects_volume = 0;
+        }
+        di+=1;
+
+        self.playing_field_width = data[di] as usize; di+=1;
+        self.playing_field_height = data[di] as usize; di+=1;
+
+        for seed_part in self.playing_field_seed.iter_mut() {
+            *seed_part = (data[di+0] as u32)<<24 | (data[di+1] as u32)<<16 | (data[di+2] as u32)<<8 | (data[di+3] as u32);
+            di+=4;
+        }
+
+        self.playing_field_data = None;
+        self.replay_data = None;
+        let pfd_len = (data[di+0] as usize)<<8 | (data[di+1] as usize);
+        if pfd_len>0 {
+            let mut pfd = Vec::<u8>::new();
+            pfd.resize(pfd_len, 0);
+            if save_access.read(LEGACY_SETTINGS_DATA_LEN, &mut pfd).is_ok() {
+                self.playing_field_data = Some(convert_legacy_playing_field_data(&pfd));
+            }
         }
         true
     }
@@ -163,17 +324,8 @@ impl Settings {
         }
 
 
-        //from RandomNumberGenerator::gen()
         for _ in 0..loops {
-            let t = self.playing_field_seed[1].wrapping_shr(9);
-
-            self.playing_field_seed[2] ^= self.playing_field_seed[0];
-            self.playing_field_seed[3] ^= self.playing_field_seed[1];
-            self.playing_field_seed[1] ^= self.playing_field_seed[2];
-            self.playing_field_seed[0] ^= self.playing_field_seed[3];
-
-            self.playing_field_seed[2] ^= t;
-            self.playing_field_seed[3] = self.playing_field_seed[3].rotate_left(11);
+            step_seed(&mut self.playing_field_seed);
         }
     
             
@@ -182,4 +334,76 @@ impl Settings {
     }
 
     
+}
+
+//from RandomNumberGenerator::gen()
+pub fn step_seed(seed : &mut [u32; 4]) {
+    let t = seed[1].wrapping_shr(9);
+
+    seed[2] ^= seed[0];
+    seed[3] ^= seed[1];
+    seed[1] ^= seed[2];
+    seed[0] ^= seed[3];
+
+    seed[2] ^= t;
+    seed[3] = seed[3].rotate_left(11);
+}
+
+
+//saved board of the first release: towers had their bounds saved, board rules, cells, clues, notes and the recording were added since
+fn convert_legacy_playing_field_data(old : &[u8]) -> Vec<u8> {
+    let area = old[0] as usize * old[1] as usize;
+    let count_towers = (old[2] as usize)<<8 | (old[3] as usize);
+    let mut data = Vec::<u8>::with_capacity(old.len());
+
+    data.extend_from_slice(&old[..4]);
+    let mut di = 4;
+    for _ in 0..count_towers {
+        data.extend_from_slice(&old[di..di+4]); //x, y, height, flattened height
+        di+=8;
+    }
+    data.extend_from_slice(&old[di..di+4*area]); di+=4*area; //field and solution
+    let mask_len = if old[di]!=0 {3 + old[di+1] as usize * old[di+2] as usize} else {1};
+    data.extend_from_slice(&old[di..di+mask_len]); di+=mask_len;
+
+    data.push(SpreadRule::Straight.as_u8());
+    data.push(0); //no wrap-around
+    data.extend_from_slice(&[0; 6]); //no cells, clues and notes
+
+    //cursor, selected tower, input mode and colors of the view
+    let view_len = 2 + (if old[di+2]!=0 {3} else {1}) + 1 + TOWER_COLORS_COUNT;
+    data.extend_from_slice(&old[di..di+view_len]);
+    data.push(0); //no recording
+    data
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::logic::PlayingField;
+
+    #[test_case]
+    fn legacy_playing_field_data_converted(_gba : &mut agb::Gba) {
+        //2x1 board, tower 0 at the left spread to the right, no mask
+        let mut old = alloc::vec![2, 1, 0, 1];
+        old.extend_from_slice(&[0, 0, 2, 1, 0, 0, 2, 1]); //x, y, height, flattened height, bounds
+        old.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]); //field and solution
+        old.push(0); //no mask
+        old.extend_from_slice(&[1, 0, 1, 0, 0, 1]); //cursor, selected tower, flatten mode
+        old.extend((0..TOWER_COLORS_COUNT).map(|i| i as u8));
+
+        let data = convert_legacy_playing_field_data(&old);
+        let mut pf = PlayingField::new(1, 1, None);
+        let di = pf.load_from_u8_vec(&data);
+
+        assert_eq!((pf.width, pf.height, pf.towers.len()), (2, 1, 1));
+        assert_eq!((pf.towers[0].height, pf.towers[0].flattened_height), (2, 1));
+        assert_eq!(pf.field[..2], [0, 0]);
+        assert!(pf.spread_rule==SpreadRule::Straight && !pf.wrap_around);
+        assert!(pf.is_solved());
+        assert_eq!(data[di..di+6], old[old.len()-6-TOWER_COLORS_COUNT..old.len()-TOWER_COLORS_COUNT]);
+        assert_eq!(data.len(), di + 6 + TOWER_COLORS_COUNT + 1);
+        assert_eq!(data[data.len()-1], 0);
+    }
 }
\ No newline at end of file
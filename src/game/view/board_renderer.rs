@@ -0,0 +1,144 @@
+use agb::display::tile_data::TileData;
+use agb::display::tiled::{RegularMap, VRamManager};
+use agb::fixnum::Vector2D;
+
+use super::super::logic::*;
+
+use super::*;
+
+
+const NUMS_PER_LIGHT_DARK_ROW : usize = 51; //tiles per row in nums.png
+const NUMS_NOTE_TOWER : usize = 2*NUMS_PER_LIGHT_DARK_ROW + 6; //frame leaving a dot of the tile below visible
+const NUMS_NOTE_BLOCKED : usize = 2*NUMS_PER_LIGHT_DARK_ROW + 7;
+const NUMS_LOCKED : usize = 2*NUMS_PER_LIGHT_DARK_ROW + 8; //padlock, light and dark next to each other
+const TILES_ROCK : usize = 34;
+const TILES_SINK : usize = 35;
+const MENU_CLUE_GLYPHS : usize = 32; //light glyphs in the 2nd row of menu.png, dark ones in the 3rd
+const MENU_PER_ROW : usize = 32;
+
+
+//draws the towers of a playing field into a tiles and a nums background
+//used by the game itself and by the example boards of the help pages
+pub struct BoardRenderer {
+    tiles : &'static TileData,
+    nums : &'static TileData,
+    tower_palette : TowerPalette,
+    tower_num_light_dark_mapping : [usize; TOWER_COLORS_COUNT],
+}
+
+impl BoardRenderer {
+
+    pub fn new(tiles : &'static TileData, nums : &'static TileData, tower_palette : TowerPalette) -> Self {
+        BoardRenderer {
+            tiles,
+            nums,
+            tower_palette,
+            tower_num_light_dark_mapping : tower_palette.calc_num_light_dark_mapping(),
+        }
+    }
+
+    //tower_colors holds the final color (0..TOWER_COLORS_COUNT) of every tower
+    //the board is drawn with its top left tile at pos
+    pub fn draw(&self, pf : &PlayingField, tower_colors : &[usize], gba_bg_tiles : &mut RegularMap, gba_bg_nums : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16)) {
+        for y in 0..pf.height {
+            for x in 0..pf.width {
+                self.draw_tile(pf, tower_colors, (x, y), gba_bg_tiles, gba_bg_nums, gba_vram, pos);
+            }
+        }
+    }
+
+    //like draw, but only the tiles at xys
+    pub fn draw_tiles(&self, pf : &PlayingField, tower_colors : &[usize], xys : &[(i32, i32)], gba_bg_tiles : &mut RegularMap, gba_bg_nums : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16)) {
+        for xy in xys {
+            self.draw_tile(pf, tower_colors, (xy.0 as usize, xy.1 as usize), gba_bg_tiles, gba_bg_nums, gba_vram, pos);
+        }
+    }
+
+    //tiles at xys drawn in the plain color (without pattern) of a tower, None => drawn empty
+    pub fn draw_preview(&self, xys : &[(i32, i32)], color : Option<usize>, gba_bg_tiles : &mut RegularMap, gba_bg_nums : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16)) {
+        let i_tiles = if let Some(col) = color {2 + col} else {1};
+        for xy in xys {
+            let tile_pos = (pos.0 + xy.0 as u16, pos.1 + xy.1 as u16);
+            gba_bg_tiles.set_tile(gba_vram, tile_pos, &self.tiles.tiles, self.tiles.tile_settings[i_tiles]);
+            gba_bg_nums.set_tile(gba_vram, tile_pos, &self.nums.tiles, self.nums.tile_settings[0]);
+        }
+    }
+
+    //tile in menu.png with the clue glyph of the tower, light or dark like the digits on its color
+    pub fn clue_tile_index(&self, clue : Clue, col : usize) -> Option<usize> {
+        let glyph = match clue {
+            Clue::None => return None,
+            Clue::Horizontal => 0,
+            Clue::Vertical => 1,
+            Clue::Arm { dir_index, len } => 2 + dir_index*(MAX_CLUE_ARM_LEN as usize + 1) + len as usize,
+        };
+        Some(MENU_CLUE_GLYPHS + self.tower_num_light_dark_mapping[col]*MENU_PER_ROW + glyph)
+    }
+
+    fn draw_tile(&self, pf : &PlayingField, tower_colors : &[usize], xy : (usize, usize), gba_bg_tiles : &mut RegularMap, gba_bg_nums : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16)) {
+        let (x, y) = xy;
+        let tower_idx = pf.field[lin2d!(x,y,pf.width)];
+        let tile_pos = (pos.0 + x as u16, pos.1 + y as u16);
+
+        let i_tiles;
+        let i_nums;
+        if tower_idx!=NO_TOWER {
+            let col = tower_colors[tower_idx as usize];
+            i_tiles = 2 + col;
+            let tower = &pf.towers[tower_idx as usize];
+            if tower.x==x && tower.y==y {
+                i_nums = 1 + tower.flattened_height as usize + self.tower_num_light_dark_mapping[col]*NUMS_PER_LIGHT_DARK_ROW;
+            } else if pf.cells[lin2d!(x,y,pf.width)]==Cell::Locked {
+                i_nums = NUMS_LOCKED + self.tower_num_light_dark_mapping[col];
+            } else {
+                i_nums = self.tower_palette.pattern_tile_index(col, self.tower_num_light_dark_mapping[col]);
+            }
+        } else if pf.cells[lin2d!(x,y,pf.width)]==Cell::Rock {
+            (i_tiles, i_nums) = (TILES_ROCK, 0);
+        } else if let Cell::Sink(_) = pf.cells[lin2d!(x,y,pf.width)] {
+            //light digits, counting down like the towers
+            (i_tiles, i_nums) = (TILES_SINK, 1 + pf.calc_sink_remaining((x as i32, y as i32)).max(0) as usize);
+        } else {
+            (i_tiles, i_nums) = match pf.note(x, y) {
+                Note::None => (1, 0),
+                Note::Tower(note_tower_idx) => (2 + tower_colors[note_tower_idx], NUMS_NOTE_TOWER),
+                Note::Blocked => (1, NUMS_NOTE_BLOCKED),
+            };
+        }
+
+        gba_bg_tiles.set_tile(gba_vram, tile_pos, &self.tiles.tiles, self.tiles.tile_settings[i_tiles]);
+        gba_bg_nums.set_tile(gba_vram, tile_pos, &self.nums.tiles, self.nums.tile_settings[i_nums]);
+    }
+}
+
+//screen position of the top left tile of a board centered on screen
+pub fn calc_centered_board_offset(pf : &PlayingField) -> Vector2D<i32> {
+    Vector2D::new(120 - pf.width as i32 * 4, 80 - pf.height as i32 * 4)
+}
+
+//screen positions of the left, right, up and down arrow sprites around the tower at tower_xy
+//in deflatten mode the arrows point inwards, so left/right and up/down swap their places
+//with bendy spreads tower_xy is the tile the tower grows from and the arrows surround it
+pub fn calc_arrow_positions(pf : &PlayingField, tower_xy : (i32, i32), off : Vector2D<i32>, deflatten : bool) -> [Vector2D<i32>; 4] {
+    if pf.spread_rule==SpreadRule::Bendy {
+        let (x, y) = (off.x+tower_xy.0*8, off.y+tower_xy.1*8);
+        return [Vector2D::new(x - 8, y), Vector2D::new(x + 8, y), Vector2D::new(x, y - 8), Vector2D::new(x, y + 8)];
+    }
+
+    let tower = &pf.towers[pf.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, pf.width)] as usize];
+
+    //on wrap-around boards an arrow beyond an edge shows up at the opposite edge
+    let wrap_x = |x : i32| if pf.wrap_around {x.rem_euclid(pf.width as i32)} else {x};
+    let wrap_y = |y : i32| if pf.wrap_around {y.rem_euclid(pf.height as i32)} else {y};
+
+    let left = Vector2D::new(off.x+wrap_x(tower.bounds.position.x - 1)*8, off.y+tower_xy.1*8);
+    let right = Vector2D::new(off.x+wrap_x(tower.bounds.position.x+tower.bounds.size.x)*8, off.y+tower_xy.1*8);
+    let up = Vector2D::new(off.x+tower_xy.0*8, off.y+wrap_y(tower.bounds.position.y - 1)*8);
+    let down = Vector2D::new(off.x+tower_xy.0*8, off.y+wrap_y(tower.bounds.position.y+tower.bounds.size.y)*8);
+
+    if deflatten {
+        [right, left, down, up]
+    } else {
+        [left, right, up, down]
+    }
+}
\ No newline at end of file
@@ -1,5 +1,9 @@
+mod board_renderer;
 mod defs;
 mod playing_field_view;
+mod tower_palettes;
 
+pub use board_renderer::*;
 pub use defs::*;
 pub use playing_field_view::*;
+pub use tower_palettes::*;
\ No newline at end of file
@@ -2,6 +2,8 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use alloc::vec;
+use alloc::format;
+use alloc::string::String;
 
 use agb::display::object::{Graphics, OamManaged, Object, Sprite, SpriteVram, TagMap};
 use agb::display::tiled::{MapLoan, RegularMap};
@@ -9,7 +11,6 @@ use agb::fixnum::Vector2D;
 use agb::input::Button;
 use agb::rng::RandomNumberGenerator;
 
-use agb::sound::mixer::{Mixer, SoundChannel};
 use agb::{include_background_gfx, include_aseprite, include_wav};
 
 use agb::display::{
@@ -17,9 +18,11 @@ use agb::display::{
         Priority,
     };
 
-use crate::game::Settings;
 use crate::util::gbaex::ButtonControllerAutoRepeat;
+use crate::util::text::{TextAlign, TextRenderer};
 use crate::assets::*;
+use crate::audio::{AudioService, SoundEvent};
+use crate::game::{Action, Replay, ReplayMove};
 
 use super::super::logic::*;
 
@@ -35,11 +38,85 @@ pub enum IngameExitMode {
 }
 
 
-#[derive(PartialEq)]
-enum PlayingFieldViewInputMode {
+#[derive(PartialEq, Clone, Copy)]
+pub enum PlayingFieldViewInputMode {
     MoveSelect,
     Flatten,
     Deflatten,
+    Drag, //a whole spread of the selected tower up to a ghost cursor gets previewed
+    Notes, //empty tiles get annotated instead of towers selected
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ForcedMovesAssist {
+    Off,
+    OnHint, //the hint button applies the next forced move before giving a hint
+    Auto,
+}
+
+pub const FORCED_MOVES_ASSISTS_COUNT : usize = 3;
+
+impl ForcedMovesAssist {
+
+    pub fn from_u8(v : u8) -> ForcedMovesAssist {
+        match v {
+            1 => ForcedMovesAssist::OnHint,
+            2 => ForcedMovesAssist::Auto,
+            _ => ForcedMovesAssist::Off,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            ForcedMovesAssist::Off => 0,
+            ForcedMovesAssist::OnHint => 1,
+            ForcedMovesAssist::Auto => 2,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForcedMovesAssist::Off => "OFF",
+            ForcedMovesAssist::OnHint => "ON HINT",
+            ForcedMovesAssist::Auto => "AUTO",
+        }
+    }
+}
+
+const MAX_UNDO_MOVES : usize = 256;
+
+const DEAD_END_MARKER_TILE : usize = 32*4; //orange corners in menu.png
+const WRAP_EDGE_TILES : [usize; 4] = [32*4 + 1, 32*4 + 2, 32*4 + 3, 32*4 + 4]; //dashed lines left, right, above and below the board
+
+const FORCED_MOVE_DELAY_FRAMES : u32 = 30; //pause between two automatically applied forced moves
+const HIGHLIGHT_FRAMES : u32 = 60; //changed tiles blink this long
+
+const SOLVED_BANNER_TEXT : &str = "You ZenFed it!";
+
+const DPAD : Button = Button::UP.union(Button::DOWN).union(Button::LEFT).union(Button::RIGHT);
+const CURSOR_ACCEL_REPEATS : u32 = 8; //auto repeats until the cursor moves one more tile per repeat
+const CURSOR_MAX_STEPS : u32 = 3;
+
+//tiles added to (flatten) or taken from (deflatten) the tower at tower_xy
+struct UndoMove {
+    tower_xy : (i32, i32),
+    xys : Vec<(i32, i32)>,
+    flatten : bool,
+}
+
+//direction of the pressed d-pad button, up/down win over left/right
+fn pressed_dir(gba_input : &ButtonControllerAutoRepeat) -> Option<(i32, i32)> {
+    if gba_input.is_just_pressed_or_auto_repeated(Button::UP) {
+        Some((0, -1))
+    } else if gba_input.is_just_pressed_or_auto_repeated(Button::DOWN) {
+        Some((0, 1))
+    } else if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) {
+        Some((-1, 0))
+    } else if gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) {
+        Some((1, 0))
+    } else {
+        None
+    }
 }
 
 pub struct PlayingFieldView<'gba> {
@@ -48,9 +125,34 @@ pub struct PlayingFieldView<'gba> {
     selected_tower_xy : Option<(i32, i32)>,
 
     input_mode : PlayingFieldViewInputMode,
+    cursor_repeats : u32, //auto repeats since a direction was pressed
+    hint_button_used : bool, //hint button was held for a cursor jump, so releasing it gives no hint
+
+    drag_deflatten : bool, //the drag takes tiles back instead of spreading
+    drag_ghost_xy : (i32, i32),
+    drag_preview_xys : Vec<(i32, i32)>, //tiles the drag would add/remove
+    drag_preview_shown_xys : Vec<(i32, i32)>, //tiles currently drawn as preview
+    gba_bg_preview_update_req : bool,
+
+    forced_moves_assist : ForcedMovesAssist,
+    forced_moves_check_req : bool, //board changed since the last search for forced moves
+    forced_move_delay : u32,
+    highlight_xys : Vec<(i32, i32)>, //tiles changed by the last forced move
+    highlight_frames : u32,
+
+    dead_end_warnings : bool,
+    dead_ends_check_req : bool,
+    dead_end_xys : Vec<(i32, i32)>, //marked on the hud layer
 
     tower_colors_mapping : [usize; TOWER_COLORS_COUNT],
-    tower_num_light_dark_mapping : [usize; TOWER_COLORS_COUNT],
+    tower_colors : Vec<usize>,
+
+    undo_moves : Vec<UndoMove>, //oldest first
+    moves_count : u32, //tiles spread or taken back by the player, undos and assisted moves are not counted
+    recording : Option<Replay>, //every applied move gets appended
+    frame_counter : u32, //frames the board has been played
+
+    board_renderer : BoardRenderer,
 
     gba_bg_bg : MapLoan<'gba, RegularMap>,
     gba_bg_bg_update_req : bool,
@@ -60,6 +162,11 @@ pub struct PlayingFieldView<'gba> {
     gba_bg_menu : MapLoan<'gba, RegularMap>,
     gba_bg_menu_update_req : bool,
 
+    text : TextRenderer,
+    hud_text : Option<&'static [&'static str]>, //replaces the solved banner, e.g. for tutorial prompts
+    status_text : Option<String>, //top right, e.g. timer and score of a session
+    pause_button_name : &'static str,
+
     gba_obj_hover : Object<'gba>,
     gba_obj_hover_anim_counter : usize,
     gba_obj_hover_anim_frame : usize,
@@ -71,10 +178,10 @@ pub struct PlayingFieldView<'gba> {
 
 impl<'gba> PlayingFieldView<'gba> {
  
-    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged) -> Self {
+    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, tower_palette : TowerPalette) -> Self {
         
 
-        gba_vram.set_background_palettes(game_bg_gfx::PALETTES);
+        tower_palette.apply(gba_vram, game_bg_gfx::PALETTES);
 
 
         let mut pfv = PlayingFieldView {
@@ -84,9 +191,34 @@ impl<'gba> PlayingFieldView<'gba> {
             selected_tower_xy: None,
 
             input_mode: PlayingFieldViewInputMode::MoveSelect,
+            cursor_repeats : 0,
+            hint_button_used : false,
+
+            drag_deflatten : false,
+            drag_ghost_xy : (0, 0),
+            drag_preview_xys : Vec::new(),
+            drag_preview_shown_xys : Vec::new(),
+            gba_bg_preview_update_req : false,
+
+            forced_moves_assist : ForcedMovesAssist::Off,
+            forced_moves_check_req : false,
+            forced_move_delay : 0,
+            highlight_xys : Vec::new(),
+            highlight_frames : 0,
+
+            dead_end_warnings : false,
+            dead_ends_check_req : false,
+            dead_end_xys : Vec::new(),
 
             tower_colors_mapping: [0; TOWER_COLORS_COUNT],
-            tower_num_light_dark_mapping: [0, 0, 0, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0],
+            tower_colors: Vec::new(),
+
+            undo_moves : Vec::new(),
+            moves_count : 0,
+            recording : None,
+            frame_counter : 0,
+
+            board_renderer : BoardRenderer::new(&game_bg_gfx::tiles, &game_bg_gfx::nums, tower_palette),
 
             gba_bg_bg : gba_tiled0.background(
                 Priority::P3,
@@ -112,6 +244,11 @@ impl<'gba> PlayingFieldView<'gba> {
             ),
             gba_bg_menu_update_req : true,
 
+            text : TextRenderer::new(&game_bg_gfx::font),
+            hud_text : None,
+            status_text : None,
+            pause_button_name : "START",
+
             gba_obj_hover : gba_oam.object_sprite(TAG_MAP.get("Hover").sprite(0)),
             gba_obj_hover_anim_counter : 0,
             gba_obj_hover_anim_frame : 0,
@@ -145,17 +282,24 @@ impl<'gba> PlayingFieldView<'gba> {
         data.push(match self.input_mode {
             PlayingFieldViewInputMode::MoveSelect => 0,
             PlayingFieldViewInputMode::Flatten => 1,
-            PlayingFieldViewInputMode::Deflatten => 2
+            PlayingFieldViewInputMode::Deflatten => 2,
+            PlayingFieldViewInputMode::Drag => if self.drag_deflatten {2} else {1}, //the drag itself is not saved
+            PlayingFieldViewInputMode::Notes => 3,
         });
         for i in 0..TOWER_COLORS_COUNT {
             data.push(self.tower_colors_mapping[i] as u8);
         }
+        data.push(if self.recording.is_some() {1} else {0});
+        if let Some(recording) = &self.recording {
+            data.extend_from_slice(&recording.save_as_u8_vec());
+        }
 
         data
     }
 
     pub fn load_from_u8_vec(&mut self, pf: &mut PlayingField, data : &Vec<u8>) -> usize {
         let mut di : usize = pf.load_from_u8_vec(data);
+        self.undo_moves.clear();
 
         self.hovered_tile_xy.0 = data[di] as i32; di+=1;
         self.hovered_tile_xy.1 = data[di] as i32; di+=1;
@@ -170,6 +314,7 @@ impl<'gba> PlayingFieldView<'gba> {
             0 => PlayingFieldViewInputMode::MoveSelect,
             1 => PlayingFieldViewInputMode::Flatten,
             2 => PlayingFieldViewInputMode::Deflatten,
+            3 => PlayingFieldViewInputMode::Notes,
             _ => PlayingFieldViewInputMode::MoveSelect
         };
         di+=1;
@@ -177,6 +322,16 @@ impl<'gba> PlayingFieldView<'gba> {
             self.tower_colors_mapping[i] = data[di+i] as usize;
         }
         di+=TOWER_COLORS_COUNT;
+        self.recording = None;
+        if data[di]!=0 {
+            let (recording, len) = Replay::load_from_u8_slice(&data[di+1..]);
+            self.frame_counter = recording.last_frame();
+            self.recording = Some(recording);
+            di+=len;
+        }
+        di+=1;
+
+        self.tower_colors = pf.calc_tower_coloring(TOWER_COLORS_COUNT);
 
         di
     }
@@ -185,29 +340,124 @@ impl<'gba> PlayingFieldView<'gba> {
         self.exit_mode
     }
 
+    pub fn get_input_mode(&self) -> PlayingFieldViewInputMode {
+        self.input_mode
+    }
+
+    pub fn get_hovered_tile_xy(&self) -> (i32, i32) {
+        self.hovered_tile_xy
+    }
+
+    pub fn set_forced_moves_assist(&mut self, assist : ForcedMovesAssist) {
+        self.forced_moves_assist = assist;
+        self.forced_moves_check_req = true;
+    }
+
+    pub fn set_dead_end_warnings(&mut self, enabled : bool) {
+        self.dead_end_warnings = enabled;
+        self.dead_ends_check_req = true;
+    }
+
+    //text lines printed centered from screen row 1 on instead of the solved banner
+    pub fn set_hud_text(&mut self, hud_text : Option<&'static [&'static str]>) {
+        self.hud_text = hud_text;
+        self.gba_bg_menu_update_req = true;
+    }
+
+    pub fn set_status_text(&mut self, status_text : Option<String>) {
+        if self.status_text!=status_text {
+            self.status_text = status_text;
+            self.gba_bg_menu_update_req = true;
+        }
+    }
+
     pub fn reset_input(&mut self) {
         self.hovered_tile_xy = (0,0);
         //self.hovered_tower_xy = None;
         self.selected_tower_xy = None;
+        self.undo_moves.clear();
+        self.moves_count = 0;
+    }
+
+    pub fn get_moves_count(&self) -> u32 {
+        self.moves_count
+    }
+
+    //replay holds the setup of the board just initialized, the moves from now on get recorded into it
+    pub fn start_recording(&mut self, replay : Replay) {
+        self.recording = Some(replay);
+        self.frame_counter = 0;
+    }
+
+    pub fn get_recording(&self) -> Option<&Replay> {
+        self.recording.as_ref()
+    }
+
+    //applies a recorded move, the cursor follows the tower
+    pub fn replay_move(&mut self, pf: &mut PlayingField, mv : &ReplayMove, audio : &mut AudioService) {
+        self.apply_move(pf, &UndoMove { tower_xy : mv.tower_xy, xys : mv.xys.clone(), flatten : mv.flatten }, audio);
+        self.focus_tower(mv.tower_xy);
     }
 
     pub fn reset_to_start_state(&mut self, pf: &mut PlayingField) {
         self.reset_input();
         pf.reset_to_start_state();
+        if let Some(recording) = &mut self.recording {
+            recording.moves.clear();
+            recording.truncated = false;
+            self.frame_counter = 0;
+        }
     }
 
     pub fn set_to_solution_state(&mut self, pf: &mut PlayingField) {
         self.reset_input();
         pf.set_to_solution_state();
+        self.recording = None; //the moves to the solution are unknown
     }
 
-    pub fn init_with_random_towers(&mut self, pf: &mut PlayingField, seed : Option<[u32; 4]>) {
+    //rocks_level and locked_level (0..OBSTACLE_LEVELS_COUNT) set how many rocks and pre-filled tiles the board gets
+    //with_clues => towers get clues until the solution is unique, with_sinks => the board gets sinks (both straight spreads only)
+    pub fn init_with_random_towers(&mut self, pf: &mut PlayingField, seed : Option<[u32; 4]>, rocks_level : u8, locked_level : u8, with_clues : bool, with_sinks : bool) {
         self.reset_input();
 
         let mut rng = if seed.is_some() {RandomNumberGenerator::new_with_seed(seed.unwrap())} else {RandomNumberGenerator::new()};
+        pf.place_random_rocks(&mut rng, rocks_level);
+        if with_sinks {
+            pf.place_random_sinks(&mut rng);
+        }
         pf.init_with_random_towers(&mut rng);
+        pf.count_sink_spreads();
+        pf.lock_random_tiles(&mut rng, locked_level);
+        if with_clues {
+            pf.add_clue_towers();
+        }
+
+        crate::util::rng::fisher_yates_shuffle_arr_inplace(&mut self.tower_colors_mapping, &mut rng);
+        self.tower_colors = pf.calc_tower_coloring(TOWER_COLORS_COUNT);
+
+        //a session replaces the solved board with a new one
+        self.input_mode = PlayingFieldViewInputMode::MoveSelect;
+        self.forced_moves_check_req = true;
+        self.dead_ends_check_req = true;
+        self.gba_bg_tiles_and_nums_update_req = true;
+        self.gba_bg_menu_update_req = true;
+        self.gba_objs_update_req = true;
+    }
+
+    //fixed board, see PlayingField::init_with_layout
+    pub fn init_with_layout(&mut self, pf: &mut PlayingField, field : &[i32], solution : &[i32], towers : &[(usize, usize, i32)]) {
+        self.reset_input();
+        self.input_mode = PlayingFieldViewInputMode::MoveSelect;
 
+        pf.init_with_layout(field, solution, towers);
+
+        let mut rng = RandomNumberGenerator::new();
         crate::util::rng::fisher_yates_shuffle_arr_inplace(&mut self.tower_colors_mapping, &mut rng);
+        self.tower_colors = pf.calc_tower_coloring(TOWER_COLORS_COUNT);
+
+        self.gba_bg_tiles_and_nums_update_req = true;
+        self.gba_bg_menu_update_req = true;
+        self.gba_objs_update_req = true;
     }
 
     fn update_gba_bgs(&mut self, pf: &PlayingField, gba_vram : &mut VRamManager) {
@@ -260,40 +510,10 @@ impl<'gba> PlayingFieldView<'gba> {
                 
             } else {
 
-                let tileset_tiles = &game_bg_gfx::tiles.tiles;
-                let tile_settings_tiles = game_bg_gfx::tiles.tile_settings;
-
-                let tileset_nums = &game_bg_gfx::nums.tiles;
-                let tile_settings_nums = game_bg_gfx::nums.tile_settings;
-
-                let mut i_tiles;
-                let mut i_nums;
-                let mut tower_idx : i32;
-                let mut pos;
-                let mut col;
-                for y in 0..pf.height {
-                    for x in 0..pf.width {
-                        tower_idx = pf.field[lin2d!(x,y,pf.width)];
-                        pos = (x as u16, y as u16);
-
-                        if tower_idx!=NO_TOWER {
-                            col = self.tower_colors_mapping[(tower_idx%32) as usize];
-                            i_tiles = 2 + col;
-                            let tower = &pf.towers[tower_idx as usize];
-                            if tower.x==x && tower.y==y {
-                                i_nums = 1 + tower.flattened_height as usize + self.tower_num_light_dark_mapping[col]*51;
-                            } else {
-                                i_nums = 0;
-                            }
-                        } else {
-                            i_tiles = 1;
-                            i_nums = 0;
-                        }
-
-                        self.gba_bg_tiles.set_tile(gba_vram, pos, tileset_tiles, tile_settings_tiles[i_tiles]);  
-                        self.gba_bg_nums.set_tile(gba_vram, pos, tileset_nums, tile_settings_nums[i_nums]);                  
-                    }
-                }
+                let tower_colors = self.calc_mapped_tower_colors();
+                self.board_renderer.draw(pf, &tower_colors, &mut self.gba_bg_tiles, &mut self.gba_bg_nums, gba_vram, (0, 0));
+                self.drag_preview_shown_xys.clear();
+                self.gba_bg_preview_update_req = true;
 
                 
                 self.gba_bg_tiles.set_visible(true);
@@ -307,6 +527,33 @@ impl<'gba> PlayingFieldView<'gba> {
             }
         }
 
+        //the drag preview blinks with the cursor
+        if self.gba_bg_preview_update_req {
+            self.gba_bg_preview_update_req = false;
+
+            if self.exit_mode==IngameExitMode::DoNotExit {
+                let tower_colors = self.calc_mapped_tower_colors();
+                self.board_renderer.draw_tiles(pf, &tower_colors, &self.drag_preview_shown_xys, &mut self.gba_bg_tiles, &mut self.gba_bg_nums, gba_vram, (0, 0));
+                self.drag_preview_shown_xys.clear();
+
+                if self.gba_obj_hover_anim_frame==0 {
+                    if self.input_mode==PlayingFieldViewInputMode::Drag {
+                        let tower_xy = self.selected_tower_xy.unwrap();
+                        let tower_idx = pf.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, pf.width)] as usize;
+                        let color = if self.drag_deflatten {None} else {Some(tower_colors[tower_idx])};
+                        self.board_renderer.draw_preview(&self.drag_preview_xys, color, &mut self.gba_bg_tiles, &mut self.gba_bg_nums, gba_vram, (0, 0));
+                        self.drag_preview_shown_xys.extend_from_slice(&self.drag_preview_xys);
+                    } else if self.highlight_frames>0 {
+                        self.board_renderer.draw_preview(&self.highlight_xys, None, &mut self.gba_bg_tiles, &mut self.gba_bg_nums, gba_vram, (0, 0));
+                        self.drag_preview_shown_xys.extend_from_slice(&self.highlight_xys);
+                    }
+                }
+
+                self.gba_bg_tiles.commit(gba_vram);
+                self.gba_bg_nums.commit(gba_vram);
+            }
+        }
+
 
         if self.gba_bg_menu_update_req {
             self.gba_bg_menu_update_req = false;
@@ -320,26 +567,69 @@ impl<'gba> PlayingFieldView<'gba> {
                 let tileset_menu = &game_bg_gfx::menu.tiles;
                 let tile_settings_menu = game_bg_gfx::menu.tile_settings;
 
-                let mut i_menu;
                 let mut pos;
 
                 for y in 0..20 {
                     for x in 0..30 {
                         pos = (x as u16, y as u16);
+                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[0]);
+                    }
+                }
 
-                        i_menu = 0;
-                        
-                        if pf.is_solved() {
-                            if self.hovered_tile_xy.1>pf.height as i32/2 && x>=2 && x<=27 && y>=0 && y<4 {
-                                i_menu = 32*5 + (y-0)*32 + (x-2);
-                            }
-                            else if self.hovered_tile_xy.1<=pf.height as i32/2 && x>=2 && x<=27 && y>=16 && y<20 {
-                                i_menu = 32*5 + (y-16)*32 + (x-2);
-                            }
+                //the hud is scrolled by the part of the board offset which is not a multiple of the tile size,
+                //so the markers line up with the board tiles
+                let off = calc_centered_board_offset(pf);
+                let tower_colors = self.calc_mapped_tower_colors();
+                for (tower_idx, tower) in pf.towers.iter().enumerate() {
+                    if let Some(i_clue) = self.board_renderer.clue_tile_index(tower.clue, tower_colors[tower_idx]) {
+                        pos = ((off.x/8 + tower.x as i32) as u16, (off.y/8 + tower.y as i32) as u16);
+                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_clue]);
+                    }
+                }
+                for xy in self.dead_end_xys.iter() {
+                    pos = ((off.x/8 + xy.0) as u16, (off.y/8 + xy.1) as u16);
+                    self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[DEAD_END_MARKER_TILE]);
+                }
+                self.gba_bg_menu.set_scroll_pos(Vector2D::new(-(off.x%8) as i16, -(off.y%8) as i16));
+
+                //wrap-around boards get dashed edges, as far as there is room around the board
+                if pf.wrap_around {
+                    let (bx, by) = (off.x/8, off.y/8);
+                    let (w, h) = (pf.width as i32, pf.height as i32);
+                    for y in by..by+h {
+                        if bx>0 {
+                            self.gba_bg_menu.set_tile(gba_vram, ((bx-1) as u16, y as u16), tileset_menu, tile_settings_menu[WRAP_EDGE_TILES[0]]);
                         }
-                        
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);                
+                        if bx+w<30 {
+                            self.gba_bg_menu.set_tile(gba_vram, ((bx+w) as u16, y as u16), tileset_menu, tile_settings_menu[WRAP_EDGE_TILES[1]]);
+                        }
+                    }
+                    for x in bx..bx+w {
+                        if by>0 {
+                            self.gba_bg_menu.set_tile(gba_vram, (x as u16, (by-1) as u16), tileset_menu, tile_settings_menu[WRAP_EDGE_TILES[2]]);
+                        }
+                        if by+h<20 {
+                            self.gba_bg_menu.set_tile(gba_vram, (x as u16, (by+h) as u16), tileset_menu, tile_settings_menu[WRAP_EDGE_TILES[3]]);
+                        }
+                    }
+                }
+
+                if self.input_mode==PlayingFieldViewInputMode::Notes {
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (1, 0), 10, "NOTES", TextAlign::Left);
+                }
+                if let Some(status_text) = &self.status_text {
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (7, 0), 22, status_text, TextAlign::Right);
+                }
+
+                if let Some(hud_text) = self.hud_text {
+                    for (y, line) in hud_text.iter().enumerate() {
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 1 + y as u16), 30, line, TextAlign::Center);
                     }
+                } else if pf.is_solved() {
+                    //banner at the edge away from the cursor, the hint below/above it
+                    let (y_banner, y_hint) = if self.hovered_tile_xy.1>pf.height as i32/2 {(2, 4)} else {(17, 15)};
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, y_banner), 30, SOLVED_BANNER_TEXT, TextAlign::Center);
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, y_hint), 30, &format!("PRESS {} TO RETURN TO MENU", self.pause_button_name), TextAlign::Center);
                 }
 
                 self.gba_bg_menu.set_visible(true);
@@ -367,8 +657,9 @@ impl<'gba> PlayingFieldView<'gba> {
 
             self.gba_obj_hover.set_sprite(gba_oam.sprite(TAG_MAP.get("Hover").sprite(self.gba_obj_hover_anim_frame)));
 
-            let off = Vector2D::new(120 - pf.width as i32 * 4, 80 - pf.height as i32 * 4);
-            self.gba_obj_hover.set_position(Vector2D::new(off.x+self.hovered_tile_xy.0*8 - 4, off.y+self.hovered_tile_xy.1*8 - 4)).show();
+            let off = calc_centered_board_offset(pf);
+            let hover_xy = if self.input_mode==PlayingFieldViewInputMode::Drag {self.drag_ghost_xy} else {self.hovered_tile_xy};
+            self.gba_obj_hover.set_position(Vector2D::new(off.x+hover_xy.0*8 - 4, off.y+hover_xy.1*8 - 4)).show();
 
 
             self.gba_obj_arrows[0].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowLeft").sprite(self.gba_obj_hover_anim_frame)));
@@ -376,28 +667,16 @@ impl<'gba> PlayingFieldView<'gba> {
             self.gba_obj_arrows[2].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowUp").sprite(self.gba_obj_hover_anim_frame)));
             self.gba_obj_arrows[3].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowDown").sprite(self.gba_obj_hover_anim_frame)));
 
-            if self.input_mode == PlayingFieldViewInputMode::MoveSelect {
+            if self.input_mode == PlayingFieldViewInputMode::MoveSelect || self.input_mode == PlayingFieldViewInputMode::Drag || self.input_mode == PlayingFieldViewInputMode::Notes {
                 self.gba_obj_arrows[0].hide();
                 self.gba_obj_arrows[1].hide();
                 self.gba_obj_arrows[2].hide();
                 self.gba_obj_arrows[3].hide();
-            } else if self.input_mode == PlayingFieldViewInputMode::Flatten {
-                let selected_tower_idx = pf.field[lin2d!(self.hovered_tile_xy.0 as usize, self.hovered_tile_xy.1 as usize, pf.width)] as usize;
-                let selected_tower = &pf.towers[selected_tower_idx];
-                
-                self.gba_obj_arrows[0].set_position(Vector2D::new(off.x+selected_tower.bounds.position.x*8 - 8, off.y+self.hovered_tile_xy.1*8)).show();
-                self.gba_obj_arrows[1].set_position(Vector2D::new(off.x+(selected_tower.bounds.position.x+selected_tower.bounds.size.x)*8, off.y+self.hovered_tile_xy.1*8)).show();
-                self.gba_obj_arrows[2].set_position(Vector2D::new(off.x+self.hovered_tile_xy.0*8, off.y+selected_tower.bounds.position.y*8 - 8)).show();
-                self.gba_obj_arrows[3].set_position(Vector2D::new(off.x+self.hovered_tile_xy.0*8, off.y+(selected_tower.bounds.position.y+selected_tower.bounds.size.y)*8)).show();
-            } else if self.input_mode == PlayingFieldViewInputMode::Deflatten {
-                let selected_tower_idx = pf.field[lin2d!(self.hovered_tile_xy.0 as usize, self.hovered_tile_xy.1 as usize, pf.width)] as usize;
-                let selected_tower = &pf.towers[selected_tower_idx];
-
-                self.gba_obj_arrows[1].set_position(Vector2D::new(off.x+selected_tower.bounds.position.x*8 - 8, off.y+self.hovered_tile_xy.1*8)).show();
-                self.gba_obj_arrows[0].set_position(Vector2D::new(off.x+(selected_tower.bounds.position.x+selected_tower.bounds.size.x)*8, off.y+self.hovered_tile_xy.1*8)).show();
-                self.gba_obj_arrows[3].set_position(Vector2D::new(off.x+self.hovered_tile_xy.0*8, off.y+selected_tower.bounds.position.y*8 - 8)).show();
-                self.gba_obj_arrows[2].set_position(Vector2D::new(off.x+self.hovered_tile_xy.0*8, off.y+(selected_tower.bounds.position.y+selected_tower.bounds.size.y)*8)).show();
-
+            } else {
+                let arrow_positions = calc_arrow_positions(pf, self.hovered_tile_xy, off, self.input_mode == PlayingFieldViewInputMode::Deflatten);
+                for i in 0..4usize {
+                    self.gba_obj_arrows[i].set_position(arrow_positions[i]).show();
+                }
             }
 
 
@@ -413,6 +692,25 @@ impl<'gba> PlayingFieldView<'gba> {
             self.gba_obj_hover_anim_counter=0;
             self.gba_obj_hover_anim_frame = (self.gba_obj_hover_anim_frame+1)%2;
             self.gba_objs_update_req=true;
+            if self.input_mode==PlayingFieldViewInputMode::Drag || self.highlight_frames>0 {
+                self.gba_bg_preview_update_req=true;
+            }
+        }
+
+        if self.highlight_frames>0 {
+            self.highlight_frames-=1;
+            if self.highlight_frames==0 {
+                self.gba_bg_preview_update_req=true;
+            }
+        }
+
+        if self.dead_ends_check_req {
+            self.dead_ends_check_req = false;
+            let dead_end_xys = if self.dead_end_warnings {pf.calc_dead_end_xys()} else {Vec::new()};
+            if dead_end_xys!=self.dead_end_xys {
+                self.dead_end_xys = dead_end_xys;
+                self.gba_bg_menu_update_req = true;
+            }
         }
 
         self.update_gba_bgs(pf, gba_vram);
@@ -780,153 +1078,424 @@ impl<'gba> PlayingFieldView<'gba> {
         pop_camera_state();*/
     }
 
-    pub fn handle_input(&mut self, pf: &mut PlayingField, gba_input : &ButtonControllerAutoRepeat, gba_mixer : &mut Mixer, game_settings : &Settings) {
+    fn apply_move(&mut self, pf: &mut PlayingField, mv : &UndoMove, audio : &mut AudioService) {
+        let solved_before = pf.is_solved();
+        let tower_idx = pf.field[lin2d!(mv.tower_xy.0 as usize, mv.tower_xy.1 as usize, pf.width)] as usize;
+        if mv.flatten {
+            pf.flatten(mv.tower_xy, mv.xys.clone());
+        } else {
+            pf.deflatten(mv.tower_xy, mv.xys.clone());
+        }
+        self.gba_bg_tiles_and_nums_update_req = true;
+        self.dead_ends_check_req = true;
+        if let Some(recording) = &mut self.recording {
+            recording.push_move(ReplayMove { frame : self.frame_counter, tower_xy : mv.tower_xy, xys : mv.xys.clone(), flatten : mv.flatten });
+        }
 
-        if self.input_mode == PlayingFieldViewInputMode::MoveSelect {
+        let height = pf.towers[tower_idx].flattened_height;
+        if mv.flatten {
+            audio.play(if height==1 {SoundEvent::TowerFlattened} else {SoundEvent::Flatten { height }});
+        } else {
+            audio.play(SoundEvent::Deflatten { height });
+        }
+        if solved_before!=pf.is_solved() {
+            self.gba_bg_menu_update_req=true;
+            if !solved_before {
+                audio.play(SoundEvent::Solved);
+            }
+        }
+    }
 
-            let mut cursor_moved = false;
-            if gba_input.is_just_pressed_or_auto_repeated(Button::UP) && self.hovered_tile_xy.1>0 {
-                self.hovered_tile_xy.1-=1;
-                if(self.hovered_tile_xy.1==pf.height as i32/2){
-                    self.gba_bg_menu_update_req=true;
+    //flatten or deflatten by the player, counted in tiles like the par of campaign levels
+    fn do_move(&mut self, pf: &mut PlayingField, mv : UndoMove, audio : &mut AudioService) {
+        self.moves_count += mv.xys.len() as u32;
+        self.do_assisted_move(pf, mv, audio);
+    }
+
+    //like do_move, but not counted, the move can be undone later on
+    fn do_assisted_move(&mut self, pf: &mut PlayingField, mv : UndoMove, audio : &mut AudioService) {
+        self.apply_move(pf, &mv, audio);
+        if self.undo_moves.len()>=MAX_UNDO_MOVES {
+            self.undo_moves.remove(0);
+        }
+        self.undo_moves.push(mv);
+        self.forced_moves_check_req = true;
+    }
+
+    //returns false if there is no forced move
+    fn do_forced_move(&mut self, pf: &mut PlayingField, audio : &mut AudioService) -> bool {
+        if let Some(fm) = pf.calc_forced_move() {
+            self.highlight_xys = fm.xys.clone();
+            self.highlight_frames = HIGHLIGHT_FRAMES;
+            self.gba_bg_preview_update_req = true;
+            self.do_assisted_move(pf, UndoMove { tower_xy : fm.tower_xy, xys : fm.xys, flatten : true }, audio);
+            true
+        } else {
+            false
+        }
+    }
+
+    //bendy spreads grow from and shrink towards the hovered tile, which walks freely over the tower
+    //returns false if nothing happened
+    fn bendy_step(&mut self, pf: &mut PlayingField, tower_xy : (i32, i32), dir : (i32, i32), audio : &mut AudioService) -> bool {
+        let flatten = self.input_mode==PlayingFieldViewInputMode::Flatten;
+        let from_xy = self.hovered_tile_xy;
+        let Some(to_xy) = pf.step_xy(from_xy, dir) else {
+            return false;
+        };
+        let evtl_xys = if flatten {
+            pf.calc_bendy_flatten_xys(tower_xy, from_xy, dir)
+        } else {
+            pf.calc_bendy_deflatten_xys(tower_xy, from_xy, dir)
+        };
+
+        if let Some(xys) = evtl_xys {
+            self.do_move(pf, UndoMove { tower_xy, xys, flatten }, audio);
+        } else if pf.field[lin2d!(to_xy.0 as usize, to_xy.1 as usize, pf.width)]==pf.field[lin2d!(tower_xy.0 as usize, tower_xy.1 as usize, pf.width)] {
+            audio.play(SoundEvent::CursorMove);
+        } else {
+            return false;
+        }
+        self.hovered_tile_xy = to_xy;
+        self.gba_objs_update_req = true;
+        true
+    }
+
+    //next note of the hovered tile
+    fn cycle_note(&mut self, pf: &mut PlayingField, audio : &mut AudioService) {
+        if pf.cycle_note(self.hovered_tile_xy) {
+            self.gba_bg_tiles_and_nums_update_req = true;
+            audio.play(SoundEvent::Select);
+        } else {
+            audio.play(SoundEvent::Blocked);
+        }
+    }
+
+    //moves the cursor onto tower_xy, in flatten/deflatten mode the tower gets selected
+    fn focus_tower(&mut self, tower_xy : (i32, i32)) {
+        self.hovered_tile_xy = tower_xy;
+        if self.input_mode==PlayingFieldViewInputMode::Flatten || self.input_mode==PlayingFieldViewInputMode::Deflatten {
+            self.selected_tower_xy = Some(tower_xy);
+        }
+        self.gba_bg_menu_update_req=true;
+        self.gba_objs_update_req=true;
+    }
+
+    fn undo(&mut self, pf: &mut PlayingField, audio : &mut AudioService) {
+        if let Some(mv) = self.undo_moves.pop() {
+            let inverse = UndoMove { tower_xy : mv.tower_xy, xys : mv.xys, flatten : !mv.flatten };
+            self.apply_move(pf, &inverse, audio);
+            self.focus_tower(inverse.tower_xy);
+            self.forced_moves_check_req = false; //otherwise the automatic assist would redo it right away
+        } else {
+            audio.play(SoundEvent::Blocked);
+        }
+    }
+
+    //selects a tower which differs from the solution in the mode needed to fix it
+    fn hint(&mut self, pf: &mut PlayingField, audio : &mut AudioService) {
+        if pf.is_solved() {
+            audio.play(SoundEvent::Blocked);
+            return;
+        }
+        if self.forced_moves_assist==ForcedMovesAssist::OnHint && self.do_forced_move(pf, audio) {
+            return;
+        }
+        if let Some((tower_idx, deflatten)) = pf.calc_hint() {
+            let tower = &pf.towers[tower_idx];
+            self.input_mode = if deflatten {PlayingFieldViewInputMode::Deflatten} else {PlayingFieldViewInputMode::Flatten};
+            self.focus_tower((tower.x as i32, tower.y as i32));
+            audio.play(SoundEvent::Select);
+        } else {
+            audio.play(SoundEvent::Blocked);
+        }
+    }
+
+    //moves the cursor up to steps tiles in dir, with jump it moves on to the next tower origin or the board edge
+    //returns false if the cursor is already at the edge
+    fn move_cursor(&mut self, pf: &PlayingField, dir : (i32, i32), steps : i32, jump : bool) -> bool {
+        let (mut x, mut y) = self.hovered_tile_xy;
+        let mut moved = 0;
+        while pf.is_inside(x+dir.0, y+dir.1) {
+            x+=dir.0;
+            y+=dir.1;
+            moved+=1;
+            if jump {
+                let tower_idx = pf.field[lin2d!(x as usize, y as usize, pf.width)];
+                if tower_idx!=NO_TOWER && pf.towers[tower_idx as usize].x==x as usize && pf.towers[tower_idx as usize].y==y as usize {
+                    break;
                 }
-                self.gba_objs_update_req=true;
-                cursor_moved = true;
+            } else if moved>=steps {
+                break;
             }
-            else if gba_input.is_just_pressed_or_auto_repeated(Button::DOWN) && self.hovered_tile_xy.1<pf.height as i32 - 1 {
-                self.hovered_tile_xy.1+=1;
-                if(self.hovered_tile_xy.1==pf.height as i32/2+1){
-                    self.gba_bg_menu_update_req=true;
-                }
-                self.gba_objs_update_req=true;
-                cursor_moved = true;
+        }
+        if moved==0 {
+            return false;
+        }
+
+        //the solved banner switches sides in the middle of the board
+        if (self.hovered_tile_xy.1>pf.height as i32/2)!=(y>pf.height as i32/2) {
+            self.gba_bg_menu_update_req=true;
+        }
+        self.hovered_tile_xy = (x, y);
+        self.gba_objs_update_req=true;
+        true
+    }
+
+    //origin of the next tower after the cursor (in reading order, wrapping around) which is not flattened completely
+    fn find_next_unfinished_tower(&self, pf: &PlayingField) -> Option<(i32, i32)> {
+        let cur = lin2d!(self.hovered_tile_xy.0 as usize, self.hovered_tile_xy.1 as usize, pf.width);
+        (1..=pf.area).map(|i| (cur+i)%pf.area).find_map(|xy| {
+            let tower_idx = pf.field[xy];
+            if tower_idx==NO_TOWER {
+                return None;
             }
+            let tower = &pf.towers[tower_idx as usize];
+            if lin2d!(tower.x, tower.y, pf.width)==xy && tower.flattened_height>1 {
+                Some((tower.x as i32, tower.y as i32))
+            } else {
+                None
+            }
+        })
+    }
 
-            if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) && self.hovered_tile_xy.0>0 {
-                self.hovered_tile_xy.0-=1;
-                self.gba_objs_update_req=true;
-                cursor_moved = true;
+    //moves the ghost cursor of a drag along the row/column of the selected tower and updates the preview
+    //returns false if the ghost cannot move in dir
+    fn move_drag_ghost(&mut self, pf: &PlayingField, dir : (i32, i32), audio : &mut AudioService) -> bool {
+        let tower_xy = self.selected_tower_xy.unwrap();
+        let Some(ghost_xy) = pf.step_xy(self.drag_ghost_xy, dir) else {
+            return false;
+        };
+        if ghost_xy.0!=tower_xy.0 && ghost_xy.1!=tower_xy.1 {
+            return false;
+        }
+        self.drag_ghost_xy = ghost_xy;
+
+        let evtl_xys = if self.drag_deflatten {
+            pf.calc_deflatten_xys(tower_xy, ghost_xy)
+        } else {
+            pf.calc_flatten_xys(tower_xy, ghost_xy)
+        };
+        let xys = evtl_xys.unwrap_or_default();
+        audio.play(if xys.len()!=self.drag_preview_xys.len() {SoundEvent::DragPreview} else {SoundEvent::CursorMove});
+        self.drag_preview_xys = xys;
+
+        self.gba_bg_preview_update_req=true;
+        self.gba_objs_update_req=true;
+        true
+    }
+
+    //back to flatten/deflatten mode of the selected tower
+    fn end_drag(&mut self) {
+        self.input_mode = if self.drag_deflatten {PlayingFieldViewInputMode::Deflatten} else {PlayingFieldViewInputMode::Flatten};
+        self.drag_preview_xys.clear();
+        self.gba_bg_preview_update_req=true;
+        self.gba_objs_update_req=true;
+    }
+
+    fn calc_mapped_tower_colors(&self) -> Vec<usize> {
+        self.tower_colors.iter().map(|c| self.tower_colors_mapping[*c]).collect()
+    }
+
+    pub fn handle_input(&mut self, pf: &mut PlayingField, gba_input : &ButtonControllerAutoRepeat, audio : &mut AudioService) {
+
+        self.frame_counter = self.frame_counter.saturating_add(1);
+
+        if self.pause_button_name!=gba_input.button_name(Action::Pause) {
+            self.pause_button_name = gba_input.button_name(Action::Pause);
+            self.gba_bg_menu_update_req=true;
+        }
+
+        //the hint button held together with a direction jumps, drags or steps in any mode,
+        //releasing it gives no hint then until it gets pressed again
+        if gba_input.is_action_just_pressed(Action::Hint) {
+            self.hint_button_used = false;
+        }
+        if gba_input.is_action_pressed(Action::Hint) && gba_input.btn_ctrl.is_pressed(DPAD) {
+            self.hint_button_used = true;
+        }
+
+        if self.input_mode == PlayingFieldViewInputMode::MoveSelect || self.input_mode == PlayingFieldViewInputMode::Notes {
+
+            //the longer a direction is held, the more tiles the cursor moves per auto repeat
+            if gba_input.btn_ctrl.is_just_pressed(DPAD) {
+                self.cursor_repeats = 0;
+            } else if gba_input.is_just_auto_repeated(Button::UP) || gba_input.is_just_auto_repeated(Button::DOWN) ||
+                gba_input.is_just_auto_repeated(Button::LEFT) || gba_input.is_just_auto_repeated(Button::RIGHT) {
+                self.cursor_repeats+=1;
             }
-            else if gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) && self.hovered_tile_xy.0<pf.width as i32 - 1 {
-                self.hovered_tile_xy.0+=1;
-                self.gba_objs_update_req=true;
-                cursor_moved = true;
+            let steps = 1 + (self.cursor_repeats/CURSOR_ACCEL_REPEATS).min(CURSOR_MAX_STEPS-1) as i32;
+            let jump = gba_input.is_action_pressed(Action::Hint);
+
+            let mut cursor_moved = false;
+            let mut cursor_blocked = false;
+            for (button, dir) in [(Button::UP, (0, -1)), (Button::DOWN, (0, 1)), (Button::LEFT, (-1, 0)), (Button::RIGHT, (1, 0))] {
+                //up/down and left/right exclude each other, up and left win
+                if (dir.1==1 && gba_input.is_just_pressed_or_auto_repeated(Button::UP)) || (dir.0==1 && gba_input.is_just_pressed_or_auto_repeated(Button::LEFT)) {
+                    continue;
+                }
+                if gba_input.is_just_pressed_or_auto_repeated(button) {
+                    if self.move_cursor(pf, dir, steps, jump) {
+                        cursor_moved = true;
+                    } else {
+                        cursor_blocked = true;
+                    }
+                }
             }
 
-            if game_settings.sound && cursor_moved {
-                let mut sc = SoundChannel::new(CURSOR_MOVE_SOUND);
-                sc.stereo();
-                gba_mixer.play_sound(sc);
+            if cursor_moved {
+                audio.play(SoundEvent::CursorMove);
+            } else if cursor_blocked && gba_input.btn_ctrl.is_just_pressed(DPAD) {
+                //only once per press, not on every auto repeat against the border
+                audio.play(SoundEvent::Blocked);
             }
 
 
-            if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B))  {
+            if self.input_mode == PlayingFieldViewInputMode::Notes {
+                if gba_input.is_action_just_pressed(Action::Flatten) {
+                    self.cycle_note(pf, audio);
+                } else if gba_input.is_action_just_pressed(Action::Deflatten) {
+                    self.input_mode = PlayingFieldViewInputMode::MoveSelect;
+                    self.gba_bg_menu_update_req=true;
+
+                    audio.play(SoundEvent::MenuBack);
+                }
+            } else if gba_input.is_action_just_pressed(Action::Flatten) || gba_input.is_action_just_pressed(Action::Deflatten) {
                 let tower_idx = pf.field[lin2d!(self.hovered_tile_xy.0 as usize,self.hovered_tile_xy.1 as usize,pf.width)];
                 if tower_idx!=NO_TOWER {
                     let tower = &pf.towers[tower_idx as usize];
                     self.selected_tower_xy = Some((tower.x as i32, tower.y as i32));
                     self.hovered_tile_xy = (tower.x as i32, tower.y as i32);
-                    self.input_mode = if gba_input.btn_ctrl.is_just_pressed(Button::A) {PlayingFieldViewInputMode::Flatten} else {PlayingFieldViewInputMode::Deflatten};
+                    self.input_mode = if gba_input.is_action_just_pressed(Action::Flatten) {PlayingFieldViewInputMode::Flatten} else {PlayingFieldViewInputMode::Deflatten};
 
-                    
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.play(SoundEvent::Select);
+                } else if gba_input.is_action_just_pressed(Action::Flatten) && pf.is_free(self.hovered_tile_xy.0, self.hovered_tile_xy.1) {
+                    //an empty tile switches to notes mode and gets its first note right away
+                    self.input_mode = PlayingFieldViewInputMode::Notes;
+                    self.gba_bg_menu_update_req=true;
+                    self.cycle_note(pf, audio);
+                } else {
+                    audio.play(SoundEvent::Blocked);
+                }
+            }
+        } else if self.input_mode == PlayingFieldViewInputMode::Drag {
+
+            //the drag usually ends before the hint button is released, so a press during the drag must not give a hint afterwards
+            if gba_input.is_action_just_pressed(Action::Hint) {
+                self.hint_button_used = true;
+            }
+
+            if let Some(dir) = pressed_dir(gba_input) {
+                if !self.move_drag_ghost(pf, dir, audio) && gba_input.btn_ctrl.is_just_pressed(DPAD) {
+                    audio.play(SoundEvent::Blocked);
                 }
             }
+
+            if gba_input.is_action_just_pressed(Action::Flatten) {
+                //apply the whole spread as one move
+                let xys = core::mem::take(&mut self.drag_preview_xys);
+                if !xys.is_empty() {
+                    let tower_xy = self.selected_tower_xy.unwrap();
+                    self.do_move(pf, UndoMove { tower_xy, xys, flatten : !self.drag_deflatten }, audio);
+                } else {
+                    audio.play(SoundEvent::Blocked);
+                }
+                self.end_drag();
+            } else if gba_input.is_action_just_pressed(Action::Deflatten) {
+                self.end_drag();
+                audio.play(SoundEvent::MenuBack);
+            }
+
         } else {
-            let mut evtl_dir = None;
-            if gba_input.is_just_pressed_or_auto_repeated(Button::UP) {
-                evtl_dir = Some((0, -1));
-            } else if gba_input.is_just_pressed_or_auto_repeated(Button::DOWN) {
-                evtl_dir = Some((0, 1));
-            } else if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) {
-                evtl_dir = Some((-1, 0));
-            } else if gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) {
-                evtl_dir = Some((1, 0));
-            }
-
-            if let Some(dir) = evtl_dir {
-                if self.input_mode==PlayingFieldViewInputMode::Flatten {
-                    if let Some(fxys) = pf.calc_flatten_towards_xys(self.selected_tower_xy.unwrap(), dir, 1) {
-                        let solved_before = pf.is_solved();
-                        pf.flatten(self.selected_tower_xy.unwrap(), fxys);
-                        self.gba_bg_tiles_and_nums_update_req = true;
-                        if game_settings.sound {
-                            let mut sc = SoundChannel::new(FLATTEN_DEFLATTEN_SOUND);
-                            sc.stereo();
-                            gba_mixer.play_sound(sc);
-                        }
-                        if !solved_before && pf.is_solved() {
-                            self.gba_bg_menu_update_req=true;
-                            
-                            if game_settings.sound {
-                                let mut sc = SoundChannel::new(SOLVED_SOUND);
-                                sc.stereo();
-                                gba_mixer.play_sound(sc);
-                            }
-                        }
+
+            if let Some(dir) = pressed_dir(gba_input) {
+                let tower_xy = self.selected_tower_xy.unwrap();
+                if pf.spread_rule==SpreadRule::Bendy {
+                    if !self.bendy_step(pf, tower_xy, dir, audio) && gba_input.btn_ctrl.is_just_pressed(DPAD) {
+                        audio.play(SoundEvent::Blocked);
                     }
-                } else if self.input_mode==PlayingFieldViewInputMode::Deflatten {
-                    if let Some(fxys) = pf.calc_deflatten_towards_xys(self.selected_tower_xy.unwrap(), (-dir.0, -dir.1), 1) {
-                        let solved_before = pf.is_solved();
-                        pf.deflatten(self.selected_tower_xy.unwrap(), fxys);
-                        self.gba_bg_tiles_and_nums_update_req = true;
-                        if game_settings.sound {
-                            let mut sc = SoundChannel::new(FLATTEN_DEFLATTEN_SOUND);
-                            sc.stereo();
-                            gba_mixer.play_sound(sc);
-                        }
-                        if solved_before && !pf.is_solved() {
-                            self.gba_bg_menu_update_req=true;
-                        }
+                } else if gba_input.is_action_pressed(Action::Hint) {
+                    //with the hint button held the spread is dragged out instead of done step by step
+                    self.drag_deflatten = self.input_mode==PlayingFieldViewInputMode::Deflatten;
+                    self.drag_ghost_xy = tower_xy;
+                    self.drag_preview_xys.clear();
+                    self.input_mode = PlayingFieldViewInputMode::Drag;
+                    if !self.move_drag_ghost(pf, dir, audio) {
+                        self.end_drag();
+                        audio.play(SoundEvent::Blocked);
+                    }
+                } else {
+                    let flatten = self.input_mode==PlayingFieldViewInputMode::Flatten;
+                    let evtl_xys = if flatten {
+                        pf.calc_flatten_towards_xys(tower_xy, dir, 1)
+                    } else {
+                        pf.calc_deflatten_towards_xys(tower_xy, (-dir.0, -dir.1), 1)
+                    };
+                    if let Some(xys) = evtl_xys {
+                        self.do_move(pf, UndoMove { tower_xy, xys, flatten }, audio);
+                    } else if gba_input.btn_ctrl.is_just_pressed(DPAD) {
+                        audio.play(SoundEvent::Blocked);
                     }
                 }
             }
 
-            if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B)) {
-                if (self.input_mode==PlayingFieldViewInputMode::Flatten && gba_input.btn_ctrl.is_just_pressed(Button::A)) ||
-                    (self.input_mode==PlayingFieldViewInputMode::Deflatten && gba_input.btn_ctrl.is_just_pressed(Button::B)) {
+            if gba_input.is_action_just_pressed(Action::Flatten) || gba_input.is_action_just_pressed(Action::Deflatten) {
+                if (self.input_mode==PlayingFieldViewInputMode::Flatten && gba_input.is_action_just_pressed(Action::Flatten)) ||
+                    (self.input_mode==PlayingFieldViewInputMode::Deflatten && gba_input.is_action_just_pressed(Action::Deflatten)) {
                     //cancel flatten/deflatten mode
                     self.input_mode = PlayingFieldViewInputMode::MoveSelect;
                     self.selected_tower_xy = None;
                     
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.play(SoundEvent::MenuBack);
 
                 } else {
                     //change flatten/deflatten mode
                     self.input_mode = if self.input_mode==PlayingFieldViewInputMode::Flatten {PlayingFieldViewInputMode::Deflatten} else {PlayingFieldViewInputMode::Flatten};
                     
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.play(SoundEvent::Select);
                 }
+                self.gba_objs_update_req=true;
             }
 
         }
 
-            
-        if gba_input.btn_ctrl.is_just_pressed(Button::START.union(Button::SELECT)) {
+        //while dragging, the drag has to be applied or cancelled first
+        if self.input_mode!=PlayingFieldViewInputMode::Drag {
+            if gba_input.is_action_just_pressed_or_auto_repeated(Action::Undo) {
+                self.undo(pf, audio);
+            }
+            else if gba_input.is_action_just_released(Action::Hint) && !self.hint_button_used {
+                self.hint(pf, audio);
+            }
+            else if gba_input.is_action_just_pressed(Action::NextTower) {
+                if let Some(tower_xy) = self.find_next_unfinished_tower(pf) {
+                    self.focus_tower(tower_xy);
+                    audio.play(SoundEvent::CursorMove);
+                } else {
+                    audio.play(SoundEvent::Blocked);
+                }
+            }
+        }
+
+        if self.forced_move_delay>0 {
+            self.forced_move_delay-=1;
+        } else if self.forced_moves_assist==ForcedMovesAssist::Auto && self.forced_moves_check_req && self.input_mode!=PlayingFieldViewInputMode::Drag {
+            if self.do_forced_move(pf, audio) {
+                self.forced_move_delay = FORCED_MOVE_DELAY_FRAMES;
+            } else {
+                self.forced_moves_check_req = false;
+            }
+        }
+
+        if gba_input.is_action_just_pressed(Action::Pause) {
             self.exit_mode = if pf.is_solved() {IngameExitMode::Exit_BoardCompleted} else {IngameExitMode::Exit_BoardNotCompleted};
             self.gba_objs_update_req=true;
             self.gba_bg_bg_update_req=true;
             self.gba_bg_tiles_and_nums_update_req=true;
             
-            if game_settings.sound {
-                let mut sc = SoundChannel::new(SELECT_SOUND);
-                sc.stereo();
-                gba_mixer.play_sound(sc);
-            }
+            audio.play(SoundEvent::Select);
         }
     }
 
 
-}
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use agb::display::palette16::Palette16;
+use agb::display::tiled::VRamManager;
+
+use super::*;
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TowerPalette {
+    Classic,
+    ColourBlind, //deuteranopia/protanopia safe
+    HighContrast,
+    Monochrome, //grey levels combined with pattern overlays
+}
+
+pub const TOWER_PALETTES_COUNT : usize = 4;
+
+const NUMS_PATTERN_OFFSET : usize = 2*51 - 1; //third row of nums.png, 3 patterns per light/dark variant
+
+//light, base and dark shade of every tower color as drawn in tiles.png
+const CLASSIC_TOWER_SHADES : [[(u8, u8, u8); 3]; TOWER_COLORS_COUNT] = [
+    [(155, 168, 202), (113, 131, 180), (80, 96, 142)],
+    [(162, 192, 225), (123, 165, 212), (89, 128, 172)],
+    [(182, 214, 239), (151, 196, 233), (116, 157, 191)],
+    [(202, 236, 247), (179, 227, 244), (141, 186, 202)],
+    [(220, 244, 244), (206, 239, 239), (166, 197, 197)],
+    [(252, 255, 236), (251, 255, 227), (208, 212, 186)],
+    [(234, 239, 236), (225, 233, 227), (184, 191, 186)],
+    [(208, 214, 217), (188, 197, 200), (150, 158, 161)],
+    [(188, 194, 200), (159, 167, 177), (123, 131, 140)],
+    [(172, 172, 191), (136, 136, 164), (101, 101, 127)],
+    [(152, 146, 175), (108, 100, 141), (76, 68, 106)],
+    [(144, 126, 156), (97, 71, 114), (65, 41, 81)],
+    [(136, 114, 136), (86, 54, 86), (55, 25, 55)],
+    [(156, 118, 140), (114, 59, 90), (81, 30, 59)],
+    [(172, 130, 150), (136, 76, 105), (101, 46, 72)],
+    [(189, 140, 152), (161, 91, 108), (125, 60, 76)],
+    [(214, 160, 160), (196, 120, 120), (157, 86, 86)],
+    [(226, 182, 171), (214, 151, 135), (174, 116, 101)],
+    [(232, 203, 183), (223, 181, 152), (182, 143, 116)],
+    [(239, 225, 196), (233, 212, 171), (191, 172, 134)],
+    [(240, 242, 213), (234, 237, 195), (192, 195, 156)],
+    [(207, 232, 195), (186, 223, 170), (148, 182, 133)],
+    [(180, 217, 187), (148, 200, 158), (112, 161, 122)],
+    [(165, 196, 185), (127, 171, 155), (93, 134, 119)],
+    [(157, 173, 185), (116, 138, 155), (82, 103, 119)],
+    [(209, 164, 168), (189, 125, 132), (150, 91, 97)],
+    [(221, 185, 185), (207, 155, 155), (167, 119, 119)],
+    [(235, 209, 204), (226, 190, 183), (185, 151, 145)],
+    [(244, 229, 222), (239, 217, 208), (197, 177, 168)],
+    [(214, 166, 177), (196, 128, 143), (157, 94, 108)],
+    [(194, 148, 169), (168, 102, 133), (131, 70, 98)],
+    [(168, 133, 162), (132, 81, 122), (97, 50, 88)],
+];
+
+//based on the Okabe-Ito palette, 8 hues in 4 lightness steps
+const COLOUR_BLIND_TOWER_COLORS : [(u8, u8, u8); TOWER_COLORS_COUNT] = [
+    (230, 159, 0), (86, 180, 233), (0, 158, 115), (240, 228, 66),
+    (0, 114, 178), (213, 94, 0), (204, 121, 167), (150, 150, 150),
+    (138, 95, 0), (51, 108, 139), (0, 94, 69), (144, 136, 39),
+    (0, 68, 106), (127, 56, 0), (122, 72, 100), (90, 90, 90),
+    (241, 202, 114), (162, 213, 242), (114, 201, 178), (246, 240, 151),
+    (114, 177, 212), (231, 166, 114), (226, 181, 206), (197, 197, 197),
+    (188, 146, 50), (102, 158, 190), (50, 145, 120), (194, 187, 90),
+    (50, 119, 157), (178, 107, 50), (172, 123, 151), (140, 140, 140),
+];
+
+const HIGH_CONTRAST_TOWER_COLORS : [(u8, u8, u8); TOWER_COLORS_COUNT] = [
+    (230, 0, 0), (255, 230, 0), (0, 200, 0), (0, 220, 230),
+    (30, 60, 255), (230, 0, 230), (255, 128, 0), (250, 250, 250),
+    (115, 0, 0), (127, 115, 0), (0, 100, 0), (0, 110, 115),
+    (15, 30, 127), (115, 0, 115), (127, 64, 0), (125, 125, 125),
+    (243, 140, 140), (255, 243, 140), (140, 230, 140), (140, 239, 243),
+    (153, 167, 255), (243, 140, 243), (255, 197, 140), (190, 190, 190),
+    (69, 0, 0), (76, 69, 0), (0, 60, 0), (0, 66, 69),
+    (9, 18, 76), (69, 0, 69), (76, 38, 0), (75, 75, 75),
+];
+
+//8 grey levels, each combined with no pattern and the 3 patterns in nums.png
+const MONOCHROME_TOWER_GREYS : [u8; 8] = [96, 116, 136, 156, 176, 196, 216, 236];
+
+
+fn rgb15(c : (u8, u8, u8)) -> u16 {
+    ((c.0 as u16)>>3) | (((c.1 as u16)>>3)<<5) | (((c.2 as u16)>>3)<<10)
+}
+
+fn shades_from_base(c : (u8, u8, u8)) -> [(u8, u8, u8); 3] {
+    //same relation as the hand drawn shades in tiles.png
+    let light = |v : u8| v + ((255-v as u16)*3/10) as u8;
+    let dark = |v : u8| ((v as u16)*3/4) as u8;
+    [(light(c.0), light(c.1), light(c.2)), c, (dark(c.0), dark(c.1), dark(c.2))]
+}
+
+//relative luminance (gamma approximated with 2.0) in 0..=65025
+fn luminance(c : (u8, u8, u8)) -> u32 {
+    let (r, g, b) = (c.0 as u32, c.1 as u32, c.2 as u32);
+    (2126*r*r + 7152*g*g + 722*b*b)/10000
+}
+
+impl TowerPalette {
+
+    pub fn from_u8(v : u8) -> TowerPalette {
+        match v {
+            1 => TowerPalette::ColourBlind,
+            2 => TowerPalette::HighContrast,
+            3 => TowerPalette::Monochrome,
+            _ => TowerPalette::Classic,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            TowerPalette::Classic => 0,
+            TowerPalette::ColourBlind => 1,
+            TowerPalette::HighContrast => 2,
+            TowerPalette::Monochrome => 3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TowerPalette::Classic => "CLASSIC",
+            TowerPalette::ColourBlind => "COLOURBLIND",
+            TowerPalette::HighContrast => "CONTRAST",
+            TowerPalette::Monochrome => "PATTERNS",
+        }
+    }
+
+    pub fn base_color(&self, col : usize) -> (u8, u8, u8) {
+        match self {
+            TowerPalette::Classic => CLASSIC_TOWER_SHADES[col][1],
+            TowerPalette::ColourBlind => COLOUR_BLIND_TOWER_COLORS[col],
+            TowerPalette::HighContrast => HIGH_CONTRAST_TOWER_COLORS[col],
+            TowerPalette::Monochrome => {
+                let g = MONOCHROME_TOWER_GREYS[col%MONOCHROME_TOWER_GREYS.len()];
+                (g, g, g)
+            },
+        }
+    }
+
+    //index into game_bg_gfx::nums for the pattern overlay of a non origin tower tile (0 => no pattern)
+    pub fn pattern_tile_index(&self, col : usize, light_dark : usize) -> usize {
+        let pattern = col/MONOCHROME_TOWER_GREYS.len();
+        if *self==TowerPalette::Monochrome && pattern>0 {
+            NUMS_PATTERN_OFFSET + pattern + light_dark*3
+        } else {
+            0
+        }
+    }
+
+    //0 => light digits, 1 => dark digits (row in nums.png)
+    pub fn calc_num_light_dark_mapping(&self) -> [usize; TOWER_COLORS_COUNT] {
+        let mut mapping = [0; TOWER_COLORS_COUNT];
+        for (col, ld) in mapping.iter_mut().enumerate() {
+            //dark digits give the better contrast as soon as the relative luminance exceeds ~0.2
+            *ld = if luminance(self.base_color(col)) > 13000 {1} else {0};
+        }
+        mapping
+    }
+
+    //sets the given background palettes with the classic tower shades replaced by the ones of this palette
+    pub fn apply(&self, gba_vram : &mut VRamManager, palettes : &[Palette16]) {
+        let mut palettes : Vec<Palette16> = palettes.to_vec();
+
+        if *self!=TowerPalette::Classic {
+            let mut replacements : Vec<(u16, u16)> = Vec::with_capacity(TOWER_COLORS_COUNT*3);
+            for (col, classic_shades) in CLASSIC_TOWER_SHADES.iter().enumerate() {
+                let shades = shades_from_base(self.base_color(col));
+                for s in 0..3 {
+                    replacements.push((rgb15(classic_shades[s]), rgb15(shades[s])));
+                }
+            }
+
+            for palette in palettes.iter_mut() {
+                for i in 1..16 {
+                    let c = palette.colour(i);
+                    if let Some(r) = replacements.iter().find(|r| r.0==c) {
+                        palette.update_colour(i, r.1);
+                    }
+                }
+            }
+        }
+
+        gba_vram.set_background_palettes(&palettes);
+    }
+}
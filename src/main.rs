@@ -7,16 +7,27 @@
 
 mod util;
 mod assets;
+mod audio;
+mod demo;
 mod game;
 mod menu;
+mod replay;
+mod tutorial;
+
+extern crate alloc;
 
 //use agb::mgba::{DebugLevel, Mgba};
+use alloc::vec::Vec;
 use agb::sound::mixer::Frequency;
 
 
-use game::{logic::PlayingField, view::IngameExitMode};
+use game::{logic::{PlayingField, SpreadRule}, view::IngameExitMode, CampaignLevel, Replay, Session, SessionEvent, SessionMode};
 use game::view::PlayingFieldView;
 use menu::{MenuExitMode, MenuView};
+use demo::DemoView;
+use replay::ReplayView;
+use tutorial::TutorialView;
+use audio::{AudioService, MusicTrack};
 use util::gbaex::ButtonControllerAutoRepeat;
 
 
@@ -29,20 +40,29 @@ fn main(mut gba: agb::Gba) -> ! {
 
     let gba_vblank = agb::interrupt::VBlank::get();
     let mut gba_input = ButtonControllerAutoRepeat::new();
-    let mut gba_mixer = gba.mixer.mixer(Frequency::Hz32768);
-    gba_mixer.enable();
 
     gba.save.init_sram();
     let mut save_access : Option<SaveData> =  gba.save.access().ok();
 
 
     let mut game_settings = game::Settings::new();
+    let mut first_launch = true; //no save data of any version yet, the tutorial gets offered
     if let Some(ref mut sd) = save_access {
-        game_settings.load(sd);
+        //saves of older versions get migrated, only an empty or corrupt sram counts as first launch
+        first_launch = !game_settings.load(sd);
+        if first_launch {
+            //the tutorial is only offered once
+            game_settings.save(sd);
+        }
     }
 
+    let mut audio = AudioService::new(gba.mixer.mixer(Frequency::Hz32768), &game_settings);
+    gba_input.apply_controls(&game_settings.controls);
+
     let mut menu_fmode = MenuExitMode::DoNotExit;
     let mut ingame_fmode = IngameExitMode::DoNotExit;
+    let mut session_result : Option<(usize, Option<usize>)> = None; //high score table and rank reached by the last session
+    let mut campaign_left = false; //the menu opens at the campaign levels
 
     loop {
 
@@ -51,17 +71,27 @@ fn main(mut gba: agb::Gba) -> ! {
             let (gba_tiled0, mut gba_vram) =  gba.display.video.tiled0();
             let gba_oam = gba.display.object.get_managed();
 
-            let mut mv = MenuView::new(&gba_tiled0, &mut gba_vram, &gba_oam, ingame_fmode == IngameExitMode::DoNotExit);
+            let mut mv = MenuView::new(&gba_tiled0, &mut gba_vram, &gba_oam, ingame_fmode == IngameExitMode::DoNotExit, first_launch, game_settings.tower_palette);
+            audio.play_music(MusicTrack::Menu);
+            first_launch = false;
+            if let Some((table, rank)) = session_result.take() {
+                mv.show_high_scores(table, rank);
+            }
+            if campaign_left {
+                campaign_left = false;
+                mv.show_campaign();
+            }
 
             loop {
-                gba_mixer.frame();
+                audio.frame(&game_settings);
 
                 gba_input.update();
 
                 game_settings.alter_seed_with_input(&gba_input);
 
-                mv.handle_input(&gba_input, &mut gba_mixer, &mut game_settings);
+                mv.handle_input(&gba_input, &mut audio, &mut game_settings);
                 mv.update(&mut gba_vram, &gba_oam, &mut game_settings);
+                gba_input.apply_controls(&game_settings.controls);
 
                 gba_vblank.wait_for_vblank();
 
@@ -72,21 +102,120 @@ fn main(mut gba: agb::Gba) -> ! {
             }
         }
 
+        //tutorial
+        if menu_fmode==MenuExitMode::Exit_StartTutorial {
+            let (gba_tiled0, mut gba_vram) =  gba.display.video.tiled0();
+            let gba_oam = gba.display.object.get_managed();
+
+            let mut tv = TutorialView::new(&gba_tiled0, &mut gba_vram, &gba_oam, &game_settings);
+            audio.play_music(MusicTrack::Ingame);
+            //the tutorial texts name the default buttons
+            gba_input.apply_controls(&game_settings.controls.with_default_buttons());
+
+            loop {
+                audio.frame(&game_settings);
+
+                gba_input.update();
+
+                tv.handle_input(&gba_input, &mut audio);
+                tv.update(&mut gba_vram, &gba_oam);
+
+                gba_vblank.wait_for_vblank();
+
+                if tv.is_finished() {
+                    break;
+                }
+            }
+            gba_input.apply_controls(&game_settings.controls);
+            continue;
+        }
+
+        //demo after the menu was left alone for a while, or the ai player going on with the saved board
+        if menu_fmode==MenuExitMode::Exit_StartDemo || menu_fmode==MenuExitMode::Exit_WatchAiSolve {
+            let (gba_tiled0, mut gba_vram) =  gba.display.video.tiled0();
+            let gba_oam = gba.display.object.get_managed();
+
+            let mut dv = if menu_fmode==MenuExitMode::Exit_WatchAiSolve {
+                //the saved board itself stays as it is
+                audio.play_music(MusicTrack::Ingame);
+                DemoView::new_watch(&gba_tiled0, &mut gba_vram, &gba_oam, &game_settings, game_settings.playing_field_data.as_ref().unwrap())
+            } else {
+                DemoView::new(&gba_tiled0, &mut gba_vram, &gba_oam, &game_settings)
+            };
+
+            loop {
+                audio.frame(&game_settings);
+
+                gba_input.update();
+
+                dv.handle_input(&gba_input, &mut audio);
+                dv.update(&mut gba_vram, &gba_oam);
+
+                gba_vblank.wait_for_vblank();
+
+                if dv.is_finished() {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        //replay of the last completed board
+        if menu_fmode==MenuExitMode::Exit_WatchReplay {
+            let (gba_tiled0, mut gba_vram) =  gba.display.video.tiled0();
+            let gba_oam = gba.display.object.get_managed();
+
+            let (replay, _) = Replay::load_from_u8_slice(game_settings.replay_data.as_ref().unwrap());
+            let mut rv = ReplayView::new(&gba_tiled0, &mut gba_vram, &gba_oam, &game_settings, replay);
+            audio.play_music(MusicTrack::Ingame);
+
+            loop {
+                audio.frame(&game_settings);
+
+                gba_input.update();
+
+                rv.handle_input(&gba_input, &mut audio);
+                rv.update(&mut gba_vram, &gba_oam);
+
+                gba_vblank.wait_for_vblank();
+
+                if rv.is_finished() {
+                    break;
+                }
+            }
+            continue;
+        }
+
         //in game
         {
             let (gba_tiled0, mut gba_vram) =  gba.display.video.tiled0();
             let gba_oam = gba.display.object.get_managed();
 
-            let mut pf = PlayingField::new(game_settings.playing_field_width, game_settings.playing_field_height, None);
-            let mut pfv = PlayingFieldView::new(&gba_tiled0, &mut gba_vram, &gba_oam);
+            let session_mode = match menu_fmode {
+                MenuExitMode::Exit_StartTimeAttack => SessionMode::TimeAttack,
+                MenuExitMode::Exit_StartZenEndless => SessionMode::ZenEndless,
+                MenuExitMode::Exit_StartCampaign => SessionMode::Campaign,
+                _ => SessionMode::Single,
+            };
+            let mut session = Session::new(session_mode, &game_settings);
+
+            let (width, height) = session.board_size();
+            let mut pf = PlayingField::new(width, height, None);
+            let mut pfv = PlayingFieldView::new(&gba_tiled0, &mut gba_vram, &gba_oam, game_settings.tower_palette);
+            pfv.set_forced_moves_assist(game_settings.forced_moves_assist);
+            pfv.set_dead_end_warnings(game_settings.dead_end_warnings);
+            audio.play_music(MusicTrack::Ingame);
             if menu_fmode==MenuExitMode::Exit_ContinueGame && game_settings.playing_field_data.is_some() {
                 pfv.load_from_u8_vec(&mut pf, game_settings.playing_field_data.as_ref().unwrap());
             } else if menu_fmode==MenuExitMode::Exit_StartNewGame {
-                pfv.init_with_random_towers(&mut pf, Some(game_settings.playing_field_seed));
+                init_new_board(&mut pf, &mut pfv, &game_settings, &session);
                 game_settings.playing_field_data = None;
                 if let Some(ref mut sd) = save_access {
                     game_settings.save(sd);
                 }
+            } else if session_mode!=SessionMode::Single {
+                //sessions leave the saved single board alone
+                init_new_board(&mut pf, &mut pfv, &game_settings, &session);
             } else {
                 unreachable!();
             }
@@ -96,23 +225,57 @@ fn main(mut gba: agb::Gba) -> ! {
             //}
 
             loop {
-                gba_mixer.frame();
+                audio.frame(&game_settings);
 
                 gba_input.update();
 
                 game_settings.alter_seed_with_input(&gba_input);
 
-                pfv.handle_input(&mut pf, &gba_input, &mut gba_mixer, &game_settings);
+                pfv.handle_input(&mut pf, &gba_input, &mut audio);
+
+                let session_event = session.frame(&pf, pfv.get_moves_count());
+                if session_event==SessionEvent::NextBoard {
+                    let (width, height) = session.board_size();
+                    pf = PlayingField::new(width, height, None);
+                    init_new_board(&mut pf, &mut pfv, &game_settings, &session);
+                } else if session_event==SessionEvent::LevelCompleted {
+                    game_settings.campaign.complete_level(session.campaign_level().unwrap(), session.stars().unwrap());
+                    game_settings.replay_data = pfv.get_recording().map(|r| r.save_as_u8_vec());
+                    if let Some(ref mut sd) = save_access {
+                        game_settings.save(sd);
+                    }
+                }
+                pfv.set_status_text(session.status_text());
+
                 pfv.update(&pf, &mut gba_vram, &gba_oam);
 
                 gba_vblank.wait_for_vblank();
 
-                if pfv.get_exit_mode() != IngameExitMode::DoNotExit {
+                if let Some(table) = session.high_score_table() {
+                    //pausing ends the session as well
+                    if session_event==SessionEvent::TimeUp || pfv.get_exit_mode() != IngameExitMode::DoNotExit {
+                        ingame_fmode = IngameExitMode::Exit_BoardNotCompleted;
+                        let rank = game_settings.high_scores.insert(table, session.score);
+                        session_result = Some((table, rank));
+                        if let Some(ref mut sd) = save_access {
+                            game_settings.save(sd);
+                        }
+                        break;
+                    }
+                } else if session.campaign_level().is_some() {
+                    //a level is started over when left unsolved
+                    if pfv.get_exit_mode() != IngameExitMode::DoNotExit {
+                        ingame_fmode = pfv.get_exit_mode();
+                        campaign_left = true;
+                        break;
+                    }
+                } else if pfv.get_exit_mode() != IngameExitMode::DoNotExit {
                     ingame_fmode = pfv.get_exit_mode();
                     if ingame_fmode==IngameExitMode::Exit_BoardNotCompleted {
                         game_settings.playing_field_data = Some(pfv.save_as_u8_vec(&pf));
                     } else {
                         game_settings.playing_field_data = None;
+                        game_settings.replay_data = pfv.get_recording().map(|r| r.save_as_u8_vec());
                     }
                     if let Some(ref mut sd) = save_access {
                         game_settings.save(sd);
@@ -122,4 +285,40 @@ fn main(mut gba: agb::Gba) -> ! {
             }  
         }
     }
+}
+
+//random board with the rules of the settings or the campaign level, the size is given by pf
+fn init_new_board(pf : &mut PlayingField, pfv : &mut PlayingFieldView, game_settings : &game::Settings, session : &Session) {
+    let replay = if let Some(level) = session.campaign_level() {
+        let level = CampaignLevel::new(level);
+        Replay {
+            width : pf.width,
+            height : pf.height,
+            spread_rule : SpreadRule::Straight,
+            wrap_around : false,
+            clue_towers : false,
+            sinks : false,
+            rocks_level : level.rocks_level,
+            locked_level : level.locked_level,
+            seed : level.seed,
+            moves : Vec::new(),
+            truncated : false,
+        }
+    } else {
+        Replay {
+            width : pf.width,
+            height : pf.height,
+            spread_rule : game_settings.spread_rule,
+            wrap_around : game_settings.wrap_around,
+            clue_towers : game_settings.clue_towers,
+            sinks : game_settings.sinks,
+            rocks_level : game_settings.rocks_level,
+            locked_level : game_settings.locked_level,
+            seed : game_settings.playing_field_seed,
+            moves : Vec::new(),
+            truncated : false,
+        }
+    };
+    replay.init_board(pf, pfv);
+    pfv.start_recording(replay);
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use crate::game::{SessionMode, Settings};
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MenuAction {
+    ContinueGame,
+    StartNewGame,
+    StartCampaign,
+    StartTimeAttack,
+    StartZenEndless,
+    WatchReplay,
+    WatchAiSolve,
+    HighScores(SessionMode),
+    HowToPlay,
+    StartTutorial,
+    Back,
+}
+
+pub enum MenuItemKind {
+    Action(MenuAction),
+    Toggle {
+        get : fn(&Settings) -> bool,
+        set : fn(&mut Settings, bool),
+    },
+    Range {
+        min : i32,
+        max : i32,
+        get : fn(&Settings) -> i32,
+        set : fn(&mut Settings, i32),
+    },
+    Choice {
+        count : usize,
+        get : fn(&Settings) -> usize,
+        set : fn(&mut Settings, usize),
+        name : fn(usize) -> &'static str,
+    },
+    Submenu(&'static Menu),
+    Info {
+        width : u16,
+        get : fn(&Settings) -> &'static str,
+    }, //value which can not be changed
+}
+
+pub struct MenuItem {
+    pub label : &'static str,
+    pub kind : MenuItemKind,
+    pub enabled : Option<fn(&Settings) -> bool>, //None => always enabled
+}
+
+pub struct Menu {
+    pub items : &'static [MenuItem],
+}
+
+pub enum MenuValue {
+    None,
+    Number(i32),
+    Text(&'static str),
+}
+
+impl MenuItem {
+
+    pub fn is_enabled(&self, settings : &Settings) -> bool {
+        self.enabled.map_or(true, |enabled| enabled(settings))
+    }
+
+    pub fn value(&self, settings : &Settings) -> MenuValue {
+        match self.kind {
+            MenuItemKind::Toggle { get, .. } => MenuValue::Text(if get(settings) {"ON"} else {"OFF"}),
+            MenuItemKind::Range { get, .. } => MenuValue::Number(get(settings)),
+            MenuItemKind::Choice { get, name, .. } => MenuValue::Text(name(get(settings))),
+            MenuItemKind::Info { get, .. } => MenuValue::Text(get(settings)),
+            _ => MenuValue::None,
+        }
+    }
+
+    //width in characters of the value field (0 => item has no value)
+    pub fn value_width(&self) -> u16 {
+        match self.kind {
+            MenuItemKind::Toggle { .. } => 3,
+            MenuItemKind::Range { min, max, .. } => {
+                let digits = |v : i32| {
+                    let mut n = v.unsigned_abs();
+                    let mut d = if v<0 {2} else {1};
+                    while n>=10 {
+                        n/=10;
+                        d+=1;
+                    }
+                    d
+                };
+                digits(min).max(digits(max))
+            },
+            MenuItemKind::Choice { count, name, .. } => (0..count).map(|c| name(c).len() as u16).max().unwrap_or(0),
+            MenuItemKind::Info { width, .. } => width,
+            _ => 0,
+        }
+    }
+
+    //steps the value by dir (-1 or 1), ranges are clamped, toggles and choices wrap around
+    //returns true if the value has changed
+    pub fn change_value(&self, settings : &mut Settings, dir : i32) -> bool {
+        match self.kind {
+            MenuItemKind::Toggle { get, set } => {
+                set(settings, !get(settings));
+                true
+            },
+            MenuItemKind::Range { min, max, get, set } => {
+                let v = get(settings);
+                let new_v = (v + dir).clamp(min, max);
+                set(settings, new_v);
+                new_v!=v
+            },
+            MenuItemKind::Choice { count, get, set, .. } => {
+                let c = get(settings) as i32;
+                set(settings, (c + dir).rem_euclid(count as i32) as usize);
+                count>1
+            },
+            _ => false,
+        }
+    }
+}
\ No newline at end of file
@@ -1,10 +1,15 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use alloc::vec;
+use alloc::format;
+
 use agb::display::object::{ OamManaged, Object};
 use agb::display::tiled::{MapLoan, RegularMap};
 use agb::fixnum::Vector2D;
 use agb::input::Button;
 use agb::rng::RandomNumberGenerator;
 
-use agb::sound::mixer::{Mixer, SoundChannel};
 
 use agb::display::{
         tiled::{Tiled0, VRamManager, RegularBackgroundSize, TiledMap},
@@ -12,53 +17,631 @@ use agb::display::{
     };
 
 use crate::assets::*;
+use crate::audio::{AudioService, SoundEvent, SoundTheme, MAX_VOLUME, SOUND_THEMES_COUNT};
 use crate::game;
-use crate::game::logic::{MAX_PLAYING_FIELD_HEIGHT, MAX_PLAYING_FIELD_WIDTH};
+use crate::game::{Action, SessionMode, CAMPAIGN_CHAPTERS_COUNT, CAMPAIGN_LEVELS_PER_CHAPTER, HIGH_SCORE_TABLE_NAMES, HIGH_SCORE_TABLES_COUNT, TIME_ATTACK_MINUTES, MAPPABLE_BUTTONS, MAPPABLE_BUTTON_NAMES, MAX_REPEAT_DELAY, MAX_REPEAT_SPEED, MIN_REPEAT_DELAY};
+use crate::game::logic::{PlayingField, SpreadRule, AI_SKILLS_COUNT, MAX_PLAYING_FIELD_HEIGHT, MAX_PLAYING_FIELD_WIDTH, OBSTACLE_LEVELS_COUNT};
 use crate::util::gbaex::ButtonControllerAutoRepeat;
+use crate::util::text::{TextAlign, TextRenderer, TextStyle};
+use crate::game::view::{calc_arrow_positions, BoardRenderer, ForcedMovesAssist, TowerPalette, FORCED_MOVES_ASSISTS_COUNT, TOWER_COLORS_COUNT, TOWER_PALETTES_COUNT};
+
+mod menu_model;
+use menu_model::*;
+
+
+
+
+
+
+const MENU_LABEL_X : u16 = 3;
+const MENU_VALUE_X : u16 = MENU_LABEL_X + 14;
+const MENU_FIRST_ITEM_Y : u16 = 6;
+const MENU_ITEM_SPACING : u16 = 2;
+const DEMO_IDLE_FRAMES : u32 = 30*60; //the demo starts when no button was pressed for this long
+
+//name (max 11 characters to fit between the value arrows), spread rule, wrap-around, clue towers and sinks of every rule variant
+const RULES : [(&str, SpreadRule, bool, bool, bool); 8] = [
+    ("STRAIGHT", SpreadRule::Straight, false, false, false),
+    ("BENDY", SpreadRule::Bendy, false, false, false),
+    ("WRAPPED", SpreadRule::Straight, true, false, false),
+    ("BENDY WRAP", SpreadRule::Bendy, true, false, false),
+    ("CLUES", SpreadRule::Straight, false, true, false),
+    ("WRAP CLUES", SpreadRule::Straight, true, true, false),
+    ("SINKS", SpreadRule::Straight, false, false, true),
+    ("WRAP SINKS", SpreadRule::Straight, true, false, true),
+];
+const OBSTACLE_LEVELS_NAMES : [&str; OBSTACLE_LEVELS_COUNT] = ["NONE", "FEW", "MANY"];
+const TIME_ATTACK_NAMES : [&str; TIME_ATTACK_MINUTES.len()] = ["5 MIN", "10 MIN"];
+const AI_SKILL_NAMES : [&str; AI_SKILLS_COUNT] = ["NOVICE", "SKILLED", "EXPERT"];
+const CAMPAIGN_STARS_NAMES : [&str; 4] = ["---", "*--", "**-", "***"];
+
+static MAIN_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "CONTINUE GAME",
+            kind : MenuItemKind::Action(MenuAction::ContinueGame),
+            enabled : Some(|s| s.playing_field_data.is_some()),
+        },
+        MenuItem {
+            label : "NEW GAME",
+            kind : MenuItemKind::Submenu(&NEW_GAME_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "CAMPAIGN",
+            kind : MenuItemKind::Submenu(&CAMPAIGN_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "CHALLENGES",
+            kind : MenuItemKind::Submenu(&CHALLENGES_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "WATCH",
+            kind : MenuItemKind::Submenu(&WATCH_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "OPTIONS",
+            kind : MenuItemKind::Submenu(&OPTIONS_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "HOW TO PLAY?",
+            kind : MenuItemKind::Submenu(&HOW_TO_PLAY_MENU),
+            enabled : None,
+        },
+    ],
+};
+
+static NEW_GAME_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "START NEW GAME",
+            kind : MenuItemKind::Action(MenuAction::StartNewGame),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BOARD WIDTH:",
+            kind : MenuItemKind::Range {
+                min : 5,
+                max : MAX_PLAYING_FIELD_WIDTH as i32,
+                get : |s| s.playing_field_width as i32,
+                set : |s, v| s.playing_field_width = v as usize,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BOARD HEIGHT:",
+            kind : MenuItemKind::Range {
+                min : 5,
+                max : MAX_PLAYING_FIELD_HEIGHT as i32,
+                get : |s| s.playing_field_height as i32,
+                set : |s, v| s.playing_field_height = v as usize,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "RULES:",
+            kind : MenuItemKind::Choice {
+                count : RULES.len(),
+                get : |s| RULES.iter().position(|r| r.1==s.spread_rule && r.2==s.wrap_around && r.3==s.clue_towers && r.4==s.sinks).unwrap_or(0),
+                set : |s, v| (_, s.spread_rule, s.wrap_around, s.clue_towers, s.sinks) = RULES[v],
+                name : |v| RULES[v].0,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "ROCKS:",
+            kind : MenuItemKind::Choice {
+                count : OBSTACLE_LEVELS_COUNT,
+                get : |s| s.rocks_level as usize,
+                set : |s, v| s.rocks_level = v as u8,
+                name : |v| OBSTACLE_LEVELS_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "PREFILLED:",
+            kind : MenuItemKind::Choice {
+                count : OBSTACLE_LEVELS_COUNT,
+                get : |s| s.locked_level as usize,
+                set : |s, v| s.locked_level = v as u8,
+                name : |v| OBSTACLE_LEVELS_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//fixed levels in chapters, a chapter opens up once the previous one is solved
+static CAMPAIGN_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "START LEVEL",
+            kind : MenuItemKind::Action(MenuAction::StartCampaign),
+            enabled : Some(|s| s.campaign.is_level_unlocked(s.campaign.level as usize)),
+        },
+        MenuItem {
+            label : "CHAPTER:",
+            kind : MenuItemKind::Range {
+                min : 1,
+                max : CAMPAIGN_CHAPTERS_COUNT as i32,
+                get : |s| (s.campaign.level as usize/CAMPAIGN_LEVELS_PER_CHAPTER) as i32 + 1,
+                set : |s, v| s.campaign.level = ((v as usize - 1)*CAMPAIGN_LEVELS_PER_CHAPTER + s.campaign.level as usize%CAMPAIGN_LEVELS_PER_CHAPTER) as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "LEVEL:",
+            kind : MenuItemKind::Range {
+                min : 1,
+                max : CAMPAIGN_LEVELS_PER_CHAPTER as i32,
+                get : |s| (s.campaign.level as usize%CAMPAIGN_LEVELS_PER_CHAPTER) as i32 + 1,
+                set : |s, v| s.campaign.level = (s.campaign.level as usize/CAMPAIGN_LEVELS_PER_CHAPTER*CAMPAIGN_LEVELS_PER_CHAPTER + v as usize - 1) as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "STARS:",
+            kind : MenuItemKind::Info {
+                width : 6,
+                get : |s| {
+                    let level = s.campaign.level as usize;
+                    if s.campaign.is_level_unlocked(level) {CAMPAIGN_STARS_NAMES[s.campaign.stars(level) as usize]} else {"LOCKED"}
+                },
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//replay of the last completed board, the ai player going on with the paused board
+static WATCH_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "WATCH REPLAY",
+            kind : MenuItemKind::Action(MenuAction::WatchReplay),
+            enabled : Some(|s| s.replay_data.is_some()),
+        },
+        MenuItem {
+            label : "WATCH AI SOLVE",
+            kind : MenuItemKind::Action(MenuAction::WatchAiSolve),
+            enabled : Some(|s| s.playing_field_data.is_some()),
+        },
+        MenuItem {
+            label : "AI SKILL:",
+            kind : MenuItemKind::Choice {
+                count : AI_SKILLS_COUNT,
+                get : |s| s.ai_skill as usize,
+                set : |s, v| s.ai_skill = v as u8,
+                name : |v| AI_SKILL_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+static CHALLENGES_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "TIME ATTACK",
+            kind : MenuItemKind::Submenu(&TIME_ATTACK_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "ZEN ENDLESS",
+            kind : MenuItemKind::Submenu(&ZEN_ENDLESS_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//solve as many boards as possible within the time limit
+static TIME_ATTACK_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "START",
+            kind : MenuItemKind::Action(MenuAction::StartTimeAttack),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BOARD WIDTH:",
+            kind : MenuItemKind::Range {
+                min : 5,
+                max : MAX_PLAYING_FIELD_WIDTH as i32,
+                get : |s| s.playing_field_width as i32,
+                set : |s, v| s.playing_field_width = v as usize,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BOARD HEIGHT:",
+            kind : MenuItemKind::Range {
+                min : 5,
+                max : MAX_PLAYING_FIELD_HEIGHT as i32,
+                get : |s| s.playing_field_height as i32,
+                set : |s, v| s.playing_field_height = v as usize,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "TIME:",
+            kind : MenuItemKind::Choice {
+                count : TIME_ATTACK_MINUTES.len(),
+                get : |s| s.time_attack_minutes_index as usize,
+                set : |s, v| s.time_attack_minutes_index = v as u8,
+                name : |v| TIME_ATTACK_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "HIGH SCORES",
+            kind : MenuItemKind::Action(MenuAction::HighScores(SessionMode::TimeAttack)),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//boards of growing size without a timer
+static ZEN_ENDLESS_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "START",
+            kind : MenuItemKind::Action(MenuAction::StartZenEndless),
+            enabled : None,
+        },
+        MenuItem {
+            label : "HIGH SCORES",
+            kind : MenuItemKind::Action(MenuAction::HighScores(SessionMode::ZenEndless)),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+static HOW_TO_PLAY_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "TUTORIAL",
+            kind : MenuItemKind::Action(MenuAction::StartTutorial),
+            enabled : None,
+        },
+        MenuItem {
+            label : "HELP PAGES",
+            kind : MenuItemKind::Action(MenuAction::HowToPlay),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//offered on first launch, skipping returns to the main menu
+static TUTORIAL_OFFER_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "PLAY TUTORIAL",
+            kind : MenuItemKind::Action(MenuAction::StartTutorial),
+            enabled : None,
+        },
+        MenuItem {
+            label : "SKIP TUTORIAL",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+static OPTIONS_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "MUSIC:",
+            kind : MenuItemKind::Range {
+                min : 0,
+                max : MAX_VOLUME as i32,
+                get : |s| s.music_volume as i32,
+                set : |s, v| s.music_volume = v as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "EFFECTS:",
+            kind : MenuItemKind::Range {
+                min : 0,
+                max : MAX_VOLUME as i32,
+                get : |s| s.effects_volume as i32,
+                set : |s, v| s.effects_volume = v as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "SOUND THEME:",
+            kind : MenuItemKind::Choice {
+                count : SOUND_THEMES_COUNT,
+                get : |s| s.sound_theme.as_u8() as usize,
+                set : |s, v| s.sound_theme = SoundTheme::from_u8(v as u8),
+                name : |v| SoundTheme::from_u8(v as u8).name(),
+            },
+            enabled : Some(|s| s.effects_volume>0),
+        },
+        MenuItem {
+            label : "COLOURS:",
+            kind : MenuItemKind::Choice {
+                count : TOWER_PALETTES_COUNT,
+                get : |s| s.tower_palette.as_u8() as usize,
+                set : |s, v| s.tower_palette = TowerPalette::from_u8(v as u8),
+                name : |v| TowerPalette::from_u8(v as u8).name(),
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "CONTROLS",
+            kind : MenuItemKind::Submenu(&CONTROLS_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "ASSISTS",
+            kind : MenuItemKind::Submenu(&ASSISTS_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+static ASSISTS_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "FORCED MOVES:",
+            kind : MenuItemKind::Choice {
+                count : FORCED_MOVES_ASSISTS_COUNT,
+                get : |s| s.forced_moves_assist.as_u8() as usize,
+                set : |s, v| s.forced_moves_assist = ForcedMovesAssist::from_u8(v as u8),
+                name : |v| ForcedMovesAssist::from_u8(v as u8).name(),
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "DEAD ENDS:",
+            kind : MenuItemKind::Toggle {
+                get : |s| s.dead_end_warnings,
+                set : |s, v| s.dead_end_warnings = v,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+static CONTROLS_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "REPEAT DELAY:",
+            kind : MenuItemKind::Range {
+                min : MIN_REPEAT_DELAY as i32,
+                max : MAX_REPEAT_DELAY as i32,
+                get : |s| s.controls.repeat_delay as i32,
+                set : |s, v| s.controls.repeat_delay = v as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "REPEAT SPEED:",
+            kind : MenuItemKind::Range {
+                min : 1,
+                max : MAX_REPEAT_SPEED as i32,
+                get : |s| s.controls.repeat_speed as i32,
+                set : |s, v| s.controls.repeat_speed = v as u8,
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BUTTONS",
+            kind : MenuItemKind::Submenu(&BUTTONS_MENU),
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
+
+//an action taking over the button of another one swaps both buttons
+static BUTTONS_MENU : Menu = Menu {
+    items : &[
+        MenuItem {
+            label : "FLATTEN:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::Flatten),
+                set : |s, v| s.controls.assign(Action::Flatten, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "DE-FLATTEN:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::Deflatten),
+                set : |s, v| s.controls.assign(Action::Deflatten, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "UNDO:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::Undo),
+                set : |s, v| s.controls.assign(Action::Undo, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "HINT:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::Hint),
+                set : |s, v| s.controls.assign(Action::Hint, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "PAUSE:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::Pause),
+                set : |s, v| s.controls.assign(Action::Pause, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "NEXT TOWER:",
+            kind : MenuItemKind::Choice {
+                count : MAPPABLE_BUTTONS.len(),
+                get : |s| s.controls.button_index(Action::NextTower),
+                set : |s, v| s.controls.assign(Action::NextTower, v),
+                name : |v| MAPPABLE_BUTTON_NAMES[v],
+            },
+            enabled : None,
+        },
+        MenuItem {
+            label : "BACK",
+            kind : MenuItemKind::Action(MenuAction::Back),
+            enabled : None,
+        },
+    ],
+};
 
+#[derive(PartialEq, Clone, Copy)]
+enum HelpBoardMode {
+    None,
+    Move,
+    Flatten,
+    Deflatten,
+}
 
+//example board of a help page, drawn with the same renderer as the game
+struct HelpBoard {
+    pos : (u16, u16), //screen tile position of the top left board tile
+    width : usize,
+    height : usize,
+    field : &'static [i32],
+    towers : &'static [(usize, usize, i32)], //origin x, y and flattened height
+    sinks : &'static [(usize, usize, i32)], //x, y and count of spreads to absorb
+    cursor : Option<(usize, usize)>,
+    mode : HelpBoardMode,
+}
 
+//text lines are printed from screen row 1 on, max 30 characters per line and 17 lines per page
+struct HelpPage {
+    text : &'static [&'static str],
+    boards : &'static [HelpBoard],
+}
 
+//HELP_PAGES is generated by build.rs from "assets/game help.txt"
+include!(concat!(env!("OUT_DIR"), "/help_pages.rs"));
 
+//step between the colors of neighbouring towers, so the examples do not only show similar shades
+const HELP_BOARD_COLOR_STEP : usize = 13;
 
-const COUNT_HELP_PAGES : i32 = 5;
+const COUNT_HELP_PAGES : i32 = HELP_PAGES.len() as i32;
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum MenuExitMode {
     DoNotExit,
     Exit_StartNewGame,
-    Exit_ContinueGame
+    Exit_ContinueGame,
+    Exit_StartTutorial,
+    Exit_StartTimeAttack,
+    Exit_StartZenEndless,
+    Exit_StartCampaign,
+    Exit_WatchReplay,
+    Exit_StartDemo,
+    Exit_WatchAiSolve,
 }
 
 
-#[derive(PartialEq, Clone, Copy)]
-enum SelectMenuItem {
-    ContinueGame,
-    StartNewGame,
-    BoardSizeX,
-    BoardSizeY,
-    Sound,
-    HowToPlay,
-}
-
 pub struct MenuView<'gba> {
 
-    selected_menu_item : SelectMenuItem,
+    menu : &'static Menu,
+    selected_item : usize,
+    parent_menus : Vec<(&'static Menu, usize)>, //menu and selected item to return to
+
+    tower_palette : TowerPalette,
 
     gba_bg_bg : MapLoan<'gba, RegularMap>,
     gba_bg_bg_update_req : bool,
+    gba_bg_tiles : MapLoan<'gba, RegularMap>,
+    gba_bg_nums : MapLoan<'gba, RegularMap>,
+    gba_bg_tiles_and_nums_update_req : bool,
     gba_bg_menu : MapLoan<'gba, RegularMap>,
-    gba_bg_help1 : MapLoan<'gba, RegularMap>,
-    gba_bg_help2 : MapLoan<'gba, RegularMap>,
     gba_bg_menu_update_req : bool,
 
+    text : TextRenderer,
+
     gba_obj_anim_counter : usize,
     gba_obj_anim_frame : usize,
     gba_obj_arrows : [Object<'gba>; 2],
+    gba_obj_hover : Object<'gba>,
+    gba_obj_board_arrows : [Object<'gba>; 4],
     gba_objs_update_req : bool,
 
     help_shown : bool,
     help_page_nr : i32,
+    help_boards : Vec<PlayingField>, //boards of the shown help page
+
+    high_scores_shown : Option<usize>, //table shown instead of the menu
+    high_scores_new_rank : Option<usize>, //entry of the session just finished
+
+    idle_frames : u32,
 
     exit_mode : MenuExitMode,
 
@@ -66,13 +649,19 @@ pub struct MenuView<'gba> {
 
 impl<'gba> MenuView<'gba> {
  
-    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, first_start : bool) -> Self {
+    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, first_start : bool, offer_tutorial : bool, tower_palette : TowerPalette) -> Self {
         
-        gba_vram.set_background_palettes(menu_bg_gfx::PALETTES);
+        tower_palette.apply(gba_vram, menu_bg_gfx::PALETTES);
+
+        let main_menu_selected_item = if first_start {1} else {0};
 
         MenuView {
 
-            selected_menu_item : if first_start {SelectMenuItem::StartNewGame} else {SelectMenuItem::ContinueGame},
+            menu : if offer_tutorial {&TUTORIAL_OFFER_MENU} else {&MAIN_MENU},
+            selected_item : if offer_tutorial {0} else {main_menu_selected_item},
+            parent_menus : if offer_tutorial {vec![(&MAIN_MENU, main_menu_selected_item)]} else {Vec::new()},
+
+            tower_palette,
 
             gba_bg_bg : gba_tiled0.background(
                 Priority::P3,
@@ -81,32 +670,48 @@ impl<'gba> MenuView<'gba> {
             ),
             gba_bg_bg_update_req : true,
 
-            gba_bg_menu : gba_tiled0.background(
+            gba_bg_tiles : gba_tiled0.background(
                 Priority::P2,
                 RegularBackgroundSize::Background32x32,
-                menu_bg_gfx::menu.tiles.format(),
+                menu_bg_gfx::tiles.tiles.format(),
             ),
-            gba_bg_help1 : gba_tiled0.background(
+            gba_bg_nums : gba_tiled0.background(
                 Priority::P1,
                 RegularBackgroundSize::Background32x32,
-                menu_bg_gfx::help1.tiles.format(),
+                menu_bg_gfx::nums.tiles.format(),
             ),
-            gba_bg_help2 : gba_tiled0.background(
+            gba_bg_tiles_and_nums_update_req : true,
+
+            gba_bg_menu : gba_tiled0.background(
                 Priority::P0,
                 RegularBackgroundSize::Background32x32,
-                menu_bg_gfx::help2.tiles.format(),
+                menu_bg_gfx::menu.tiles.format(),
             ),
             gba_bg_menu_update_req : true,
 
+            text : TextRenderer::new(&menu_bg_gfx::font),
+
             gba_obj_anim_counter : 0,
             gba_obj_anim_frame : 0,
             gba_obj_arrows : [
                 gba_oam.object_sprite(TAG_MAP.get("HelpArrowUp").sprite(0)),
                 gba_oam.object_sprite(TAG_MAP.get("HelpArrowDown").sprite(0)) ],
+            gba_obj_hover : gba_oam.object_sprite(TAG_MAP.get("Hover").sprite(0)),
+            gba_obj_board_arrows : [
+                gba_oam.object_sprite(TAG_MAP.get("ArrowLeft").sprite(0)),
+                gba_oam.object_sprite(TAG_MAP.get("ArrowRight").sprite(0)),
+                gba_oam.object_sprite(TAG_MAP.get("ArrowUp").sprite(0)),
+                gba_oam.object_sprite(TAG_MAP.get("ArrowDown").sprite(0)) ],
             gba_objs_update_req : true,
 
             help_shown : false,
             help_page_nr : 0,
+            help_boards : Vec::new(),
+
+            high_scores_shown : None,
+            high_scores_new_rank : None,
+
+            idle_frames : 0,
 
             exit_mode : MenuExitMode::DoNotExit,
 
@@ -154,41 +759,65 @@ impl<'gba> MenuView<'gba> {
             }
         }
 
+        if self.gba_bg_tiles_and_nums_update_req {
+            self.gba_bg_tiles_and_nums_update_req = false;
+
+            if self.exit_mode!=MenuExitMode::DoNotExit || !self.help_shown {
+                self.gba_bg_tiles.set_visible(false);
+                self.gba_bg_tiles.commit(gba_vram);
+
+                self.gba_bg_nums.set_visible(false);
+                self.gba_bg_nums.commit(gba_vram);
+
+            } else {
+
+                let mut pos;
+
+                for y in 0..20 {
+                    for x in 0..30 {
+                        pos = (x as u16, y as u16);
+                        self.gba_bg_tiles.set_tile(gba_vram, pos, &menu_bg_gfx::tiles.tiles, menu_bg_gfx::tiles.tile_settings[0]);
+                        self.gba_bg_nums.set_tile(gba_vram, pos, &menu_bg_gfx::nums.tiles, menu_bg_gfx::nums.tile_settings[0]);
+                    }
+                }
+
+                //example boards
+                let board_renderer = BoardRenderer::new(&menu_bg_gfx::tiles, &menu_bg_gfx::nums, self.tower_palette);
+                let help_page = &HELP_PAGES[self.help_page_nr as usize];
+                for (board, pf) in help_page.boards.iter().zip(self.help_boards.iter()) {
+                    let tower_colors : Vec<usize> = pf.calc_tower_coloring(TOWER_COLORS_COUNT).iter()
+                        .map(|c| (c*HELP_BOARD_COLOR_STEP)%TOWER_COLORS_COUNT)
+                        .collect();
+                    board_renderer.draw(pf, &tower_colors, &mut self.gba_bg_tiles, &mut self.gba_bg_nums, gba_vram, board.pos);
+                }
+
+                self.gba_bg_tiles.set_visible(true);
+                self.gba_bg_tiles.commit(gba_vram);
+
+                self.gba_bg_nums.set_visible(true);
+                self.gba_bg_nums.commit(gba_vram);
+            }
+        }
+
         if self.gba_bg_menu_update_req {
             self.gba_bg_menu_update_req = false;
             if self.exit_mode!=MenuExitMode::DoNotExit {
                 self.gba_bg_menu.set_visible(false);
                 self.gba_bg_menu.commit(gba_vram);
 
-                self.gba_bg_help1.set_visible(false);
-                self.gba_bg_help2.set_visible(false);
-                self.gba_bg_help1.commit(gba_vram);
-                self.gba_bg_help2.commit(gba_vram);
-
             } else {
 
                 let tileset_menu = &menu_bg_gfx::menu.tiles;
                 let tile_settings_menu = menu_bg_gfx::menu.tile_settings;
 
-                let tileset_help1 = &menu_bg_gfx::help1.tiles;
-                let tile_settings_help1 = menu_bg_gfx::help1.tile_settings;
-                let tileset_help2 = &menu_bg_gfx::help2.tiles;
-                let tile_settings_help2 = menu_bg_gfx::help2.tile_settings;
-
                 let mut i_menu;
-                let mut i_help1;
-                let mut i_help2;
                 let mut pos;
 
                 for y in 0..20 {
                     for x in 0..30 {
                         pos = (x as u16, y as u16);
                         i_menu=0;
-                        i_help1=29;
-                        i_help2=29;
                         self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);   
-                        self.gba_bg_help1.set_tile(gba_vram, pos, tileset_help1, tile_settings_help1[i_help1]);
-                        self.gba_bg_help2.set_tile(gba_vram, pos, tileset_help2, tile_settings_help2[i_help2]);                
                     }
                 }
 
@@ -197,38 +826,36 @@ impl<'gba> MenuView<'gba> {
 
                     //help menu 
 
+                    let help_page = &HELP_PAGES[self.help_page_nr as usize];
+
                     //text
-                    let ty_per_page = [0, 12, 23, 37, 51];
-                    let th_per_page = [11, 10, 13, 13, 8 ];
-                    for y in 0..th_per_page[self.help_page_nr as usize] {
-                        for x in 0..30 {
-                            pos = (x as u16, (y+1) as u16);
-                            let ty = ty_per_page[self.help_page_nr as usize] + y;
-                            if ty>=34 {
-                                i_help2 = (ty-34)*30 + x ;
-                                self.gba_bg_help2.set_tile(gba_vram, pos, tileset_help2, tile_settings_help2[i_help2]);  
-                            } else {
-                                i_help1 = ty*30 + x ;
-                                self.gba_bg_help1.set_tile(gba_vram, pos, tileset_help1, tile_settings_help1[i_help1]);              
-                            }
-                        }
+                    for (y, line) in help_page.text.iter().enumerate() {
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 1 + y as u16), 30, line, TextAlign::Left);
                     }
 
                     //page nr
-                    {
-                        pos = (13, 18);
-                        i_menu = 128+self.help_page_nr as usize +1;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (14, 18);
-                        i_menu = 128+31;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (15, 18);
-                        i_menu = 128+COUNT_HELP_PAGES as usize;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
+                    self.text.print_number(&mut self.gba_bg_menu, gba_vram, (0, 18), 14, self.help_page_nr + 1, TextAlign::Right);
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (14, 18), 1, "/", TextAlign::Left);
+                    self.text.print_number(&mut self.gba_bg_menu, gba_vram, (15, 18), 15, COUNT_HELP_PAGES, TextAlign::Left);
+
+                } else if let Some(table) = self.high_scores_shown {
+
+                    //high scores of one table, left/right switches tables
+
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 2), 30, &format!("< {} >", HIGH_SCORE_TABLE_NAMES[table]), TextAlign::Center);
+                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 6), 30, "   BOARDS TOWERS  SIZE", TextAlign::Center);
+
+                    for (i, e) in game_settings.high_scores.table(table).iter().enumerate() {
+                        let y = 8 + 2*i as u16;
+                        let line = if e.boards==0 {
+                            format!("{}. {:>6} {:>6} {:>5}", i + 1, "-", "-", "-")
+                        } else {
+                            format!("{}. {:>6} {:>6} {:>5}", i + 1, e.boards, e.towers, format!("{}X{}", e.width, e.height))
+                        };
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, y), 30, &line, TextAlign::Center);
+                        if self.high_scores_new_rank==Some(i) {
+                            self.set_item_arrow(gba_vram, y, true);
+                        }
                     }
 
                 } else {
@@ -245,143 +872,39 @@ impl<'gba> MenuView<'gba> {
                     }
 
 
-                    let l = 11;
+                    let mut y = MENU_FIRST_ITEM_Y;
+                    for (i, item) in self.menu.items.iter().enumerate() {
+                        let selected = i==self.selected_item;
+                        self.set_item_arrow(gba_vram, y, selected);
 
-                    let mut y = 6;
-                    // continue game
-                    {
-                        pos = (l - 2 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::ContinueGame {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..8 {
-                        pos = (l + x as u16, y as u16);
-                        i_menu = (if game_settings.playing_field_data.is_none() {64} else {96}) + 9 + x;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
-                    }
-                    y+=2;
-
-                    // start new game
-                    {
-                        pos = (l - 2 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::StartNewGame {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..8 {
-                        pos = (l + x as u16, y as u16);
-                        i_menu = 96+x;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
-                    }
-                    y+=2;
+                        self.text.set_style(if item.is_enabled(game_settings) {TextStyle::Normal} else {TextStyle::Disabled});
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (MENU_LABEL_X, y), MENU_VALUE_X - MENU_LABEL_X, item.label, TextAlign::Left);
 
-                    // board size
-                    {
-                        pos = (l - 2 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::BoardSizeX || self.selected_menu_item==SelectMenuItem::BoardSizeY {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..6 {
-                        pos = (l + x as u16, y as u16);
-                        i_menu = 32+x;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
-                    }
-                    // x
-                    {
-                        pos = (l + 6 as u16, y as u16);
-                        i_menu = 32+6;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 7 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::BoardSizeX {1} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 8 as u16, y as u16);
-                        i_menu = 128+game_settings.playing_field_width;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 9 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::BoardSizeX {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    y+=1;
-                    // y
-                    {
-                        pos = (l + 6 as u16, y as u16);
-                        i_menu = 32+7;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 7 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::BoardSizeY {1} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 8 as u16, y as u16);
-                        i_menu = 128+game_settings.playing_field_height;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 9 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::BoardSizeY {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    y+=2;
-                    
-
-
-
-                    // sound
-                    {
-                        pos = (l - 2 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::Sound {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..3 {
-                        pos = (l + x as u16, y as u16);
-                        i_menu = 2*32+17+x;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
-                    }
-                    {
-                        pos = (l + 3 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::Sound {1} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..2 {
-                        pos = (l + 4 + x as u16, y as u16);
-                        i_menu = 2*32+20+x + (if game_settings.sound {0} else {32});
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    {
-                        pos = (l + 6 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::Sound {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    y+=2;
+                        let width = item.value_width();
+                        if width>0 {
+                            if !matches!(item.kind, MenuItemKind::Info { .. }) {
+                                self.set_value_arrows(gba_vram, y, width, selected);
+                            }
+                            match item.value(game_settings) {
+                                MenuValue::Number(n) => {
+                                    self.text.print_number(&mut self.gba_bg_menu, gba_vram, (MENU_VALUE_X + 1, y), width, n, TextAlign::Right);
+                                },
+                                MenuValue::Text(t) => {
+                                    self.text.print_str(&mut self.gba_bg_menu, gba_vram, (MENU_VALUE_X + 1, y), width, t, TextAlign::Left);
+                                },
+                                MenuValue::None => {},
+                            }
+                        }
+                        self.text.set_style(TextStyle::Normal);
 
-                    // how to play
-                    {
-                        pos = (l - 2 as u16, y as u16);
-                        i_menu = if self.selected_menu_item==SelectMenuItem::HowToPlay {2} else {0};
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]); 
-                    }
-                    for x in 0..7 {
-                        pos = (l + x as u16, y as u16);
-                        i_menu = 64+x;
-                        self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
+                        y+=MENU_ITEM_SPACING;
                     }
-                    y+=2;
 
 
-                    // credits
-                    for y in 0..2 {
-                        for x in 0..14 {
-                            pos = ((30-14+x) as u16, (20-2+y) as u16);
-                            i_menu = y*32 + 8+x;
-                            self.gba_bg_menu.set_tile(gba_vram, pos, tileset_menu, tile_settings_menu[i_menu]);              
-                        }
+                    // credits, unless the menu reaches down to them
+                    if y<=18 {
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 18), 30, "by gordonshamway23@itch.io", TextAlign::Right);
+                        self.text.print_str(&mut self.gba_bg_menu, gba_vram, (0, 19), 30, "and WTBasil@itch.io", TextAlign::Right);
                     }
 
                 }
@@ -389,17 +912,26 @@ impl<'gba> MenuView<'gba> {
                 self.gba_bg_menu.set_visible(true);
                 self.gba_bg_menu.commit(gba_vram);
 
-                self.gba_bg_help1.set_visible(true);
-                self.gba_bg_help2.set_visible(true);
-                self.gba_bg_help1.commit(gba_vram);
-                self.gba_bg_help2.commit(gba_vram);
-
             }
         }
 
 
     }
 
+    //arrow in front of the menu item label
+    fn set_item_arrow(&mut self, gba_vram : &mut VRamManager, y : u16, selected : bool) {
+        let i_menu = if selected {2} else {0};
+        self.gba_bg_menu.set_tile(gba_vram, (MENU_LABEL_X - 2, y), &menu_bg_gfx::menu.tiles, menu_bg_gfx::menu.tile_settings[i_menu]);
+    }
+
+    //arrows left and right of the value field of the given width
+    fn set_value_arrows(&mut self, gba_vram : &mut VRamManager, y : u16, width : u16, selected : bool) {
+        let i_menu_left = if selected {1} else {0};
+        let i_menu_right = if selected {2} else {0};
+        self.gba_bg_menu.set_tile(gba_vram, (MENU_VALUE_X, y), &menu_bg_gfx::menu.tiles, menu_bg_gfx::menu.tile_settings[i_menu_left]);
+        self.gba_bg_menu.set_tile(gba_vram, (MENU_VALUE_X + 1 + width, y), &menu_bg_gfx::menu.tiles, menu_bg_gfx::menu.tile_settings[i_menu_right]);
+    }
+
 
     fn update_gba_objs(&mut self, gba_oam : &OamManaged) {
 
@@ -410,6 +942,10 @@ impl<'gba> MenuView<'gba> {
                 for i in 0..2usize {
                     self.gba_obj_arrows[i].hide();
                 }
+                self.gba_obj_hover.hide();
+                for i in 0..4usize {
+                    self.gba_obj_board_arrows[i].hide();
+                }
                 gba_oam.commit();
                 return;
             }
@@ -430,6 +966,36 @@ impl<'gba> MenuView<'gba> {
                 self.gba_obj_arrows[1].set_position(Vector2D::new(29*8, 19*8)).show();
             } 
 
+            //cursor and arrows of the example board showing a mode
+            self.gba_obj_hover.hide();
+            for i in 0..4usize {
+                self.gba_obj_board_arrows[i].hide();
+            }
+
+            let help_page = &HELP_PAGES[self.help_page_nr as usize];
+            for (board, pf) in help_page.boards.iter().zip(self.help_boards.iter()) {
+                let Some(cursor) = board.cursor else {
+                    continue;
+                };
+                let cursor = (cursor.0 as i32, cursor.1 as i32);
+                let off = Vector2D::new(board.pos.0 as i32 * 8, board.pos.1 as i32 * 8);
+
+                self.gba_obj_hover.set_sprite(gba_oam.sprite(TAG_MAP.get("Hover").sprite(self.gba_obj_anim_frame)));
+                self.gba_obj_hover.set_position(Vector2D::new(off.x+cursor.0*8 - 4, off.y+cursor.1*8 - 4)).show();
+
+                if board.mode==HelpBoardMode::Flatten || board.mode==HelpBoardMode::Deflatten {
+                    self.gba_obj_board_arrows[0].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowLeft").sprite(self.gba_obj_anim_frame)));
+                    self.gba_obj_board_arrows[1].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowRight").sprite(self.gba_obj_anim_frame)));
+                    self.gba_obj_board_arrows[2].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowUp").sprite(self.gba_obj_anim_frame)));
+                    self.gba_obj_board_arrows[3].set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowDown").sprite(self.gba_obj_anim_frame)));
+
+                    let arrow_positions = calc_arrow_positions(pf, cursor, off, board.mode==HelpBoardMode::Deflatten);
+                    for i in 0..4usize {
+                        self.gba_obj_board_arrows[i].set_position(arrow_positions[i]).show();
+                    }
+                }
+            }
+
 
             gba_oam.commit();
         }
@@ -437,6 +1003,12 @@ impl<'gba> MenuView<'gba> {
 
     pub fn update(&mut self, gba_vram : &mut VRamManager, gba_oam : &OamManaged, game_settings : &mut game::Settings) {
 
+        if self.tower_palette!=game_settings.tower_palette {
+            self.tower_palette = game_settings.tower_palette;
+            self.tower_palette.apply(gba_vram, menu_bg_gfx::PALETTES);
+            self.gba_bg_tiles_and_nums_update_req=true;
+        }
+
         self.gba_obj_anim_counter+=1;
         if self.gba_obj_anim_counter>=10 {
             self.gba_obj_anim_counter=0;
@@ -449,188 +1021,212 @@ impl<'gba> MenuView<'gba> {
 
     }
 
-    pub fn handle_input(&mut self, gba_input : &ButtonControllerAutoRepeat, gba_mixer : &mut Mixer, game_settings : &mut game::Settings) {
+    pub fn handle_input(&mut self, gba_input : &ButtonControllerAutoRepeat, audio : &mut AudioService, game_settings : &mut game::Settings) {
 
-        if self.help_shown {
+        //not while reading the help or the high scores
+        if gba_input.btn_ctrl.is_pressed(Button::all()) || self.help_shown || self.high_scores_shown.is_some() {
+            self.idle_frames = 0;
+        } else {
+            self.idle_frames += 1;
+            if self.idle_frames>=DEMO_IDLE_FRAMES {
+                self.exit_mode = MenuExitMode::Exit_StartDemo;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+                return;
+            }
+        }
+
+        if let Some(table) = self.high_scores_shown {
+
+            if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B).union(Button::START).union(Button::SELECT)) {
+                self.high_scores_shown=None;
+                self.high_scores_new_rank=None;
+                self.gba_bg_menu_update_req=true;
+
+                audio.play(SoundEvent::MenuBack);
+            }
+            else if gba_input.btn_ctrl.is_just_pressed(Button::LEFT) || gba_input.btn_ctrl.is_just_pressed(Button::RIGHT) {
+                let dir = if gba_input.btn_ctrl.is_just_pressed(Button::LEFT) {HIGH_SCORE_TABLES_COUNT - 1} else {1};
+                self.show_high_scores((table + dir)%HIGH_SCORE_TABLES_COUNT, None);
+
+                audio.play(SoundEvent::CursorMove);
+            }
+        } else if self.help_shown {
 
             if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B).union(Button::START).union(Button::SELECT)) {
                 self.help_shown=false;
+                self.gba_bg_tiles_and_nums_update_req=true;
                 self.gba_bg_menu_update_req=true;
                 self.gba_objs_update_req=true;
 
-                if game_settings.sound {
-                    let mut sc = SoundChannel::new(&SELECT_SOUND);
-                    sc.stereo();
-                    gba_mixer.play_sound(sc);
-                }
+                audio.play(SoundEvent::MenuBack);
             }
             else if gba_input.btn_ctrl.is_just_pressed(Button::UP) {
                 if self.help_page_nr>0 {
-                    self.help_page_nr -= 1;
-                    self.gba_bg_menu_update_req=true;
-                    self.gba_objs_update_req=true;
+                    self.show_help_page(self.help_page_nr - 1);
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.play(SoundEvent::CursorMove);
                 }
             }
             else if gba_input.btn_ctrl.is_just_pressed(Button::DOWN) {
                 if self.help_page_nr<COUNT_HELP_PAGES-1 {
-                    self.help_page_nr += 1;
-                    self.gba_bg_menu_update_req=true;
-                    self.gba_objs_update_req=true;
+                    self.show_help_page(self.help_page_nr + 1);
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.play(SoundEvent::CursorMove);
                 }
             }
         } else {
+
+            let count_items = self.menu.items.len();
             
             if gba_input.is_just_pressed_or_auto_repeated(Button::UP) {
-                if self.selected_menu_item!=SelectMenuItem::ContinueGame {
-                    self.selected_menu_item = match self.selected_menu_item {
-                        SelectMenuItem::ContinueGame => SelectMenuItem::ContinueGame,
-                        SelectMenuItem::StartNewGame => SelectMenuItem::ContinueGame,
-                        SelectMenuItem::BoardSizeX => SelectMenuItem::StartNewGame,
-                        SelectMenuItem::BoardSizeY => SelectMenuItem::BoardSizeX,
-                        SelectMenuItem::Sound => SelectMenuItem::BoardSizeY,
-                        SelectMenuItem::HowToPlay => SelectMenuItem::Sound,
-                    };
-                    self.gba_bg_menu_update_req=true;
-
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                }
+                self.selected_item = (self.selected_item + count_items - 1)%count_items;
+                self.gba_bg_menu_update_req=true;
+                audio.play(SoundEvent::CursorMove);
             }
             else if gba_input.is_just_pressed_or_auto_repeated(Button::DOWN) {
-                if self.selected_menu_item!=SelectMenuItem::HowToPlay {
-                    self.selected_menu_item = match self.selected_menu_item {
-                        SelectMenuItem::ContinueGame => SelectMenuItem::StartNewGame,
-                        SelectMenuItem::StartNewGame => SelectMenuItem::BoardSizeX,
-                        SelectMenuItem::BoardSizeX => SelectMenuItem::BoardSizeY,
-                        SelectMenuItem::BoardSizeY => SelectMenuItem::Sound,
-                        SelectMenuItem::Sound => SelectMenuItem::HowToPlay,
-                        SelectMenuItem::HowToPlay => SelectMenuItem::HowToPlay,
-                    };
+                self.selected_item = (self.selected_item + 1)%count_items;
+                self.gba_bg_menu_update_req=true;
+                audio.play(SoundEvent::CursorMove);
+            }
+            else if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) || gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) {
+                let dir = if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) {-1} else {1};
+                let item = &self.menu.items[self.selected_item];
+                if item.is_enabled(game_settings) && item.change_value(game_settings, dir) {
                     self.gba_bg_menu_update_req=true;
-
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
+                    audio.apply_settings(game_settings);
+                    audio.play(SoundEvent::CursorMove);
                 }
             }
-            else if gba_input.is_just_pressed_or_auto_repeated(Button::LEFT) {
-                if self.selected_menu_item == SelectMenuItem::BoardSizeX && game_settings.playing_field_width>5 {
-                    game_settings.playing_field_width -=1;
-                    self.gba_bg_menu_update_req=true;
-
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                } else if self.selected_menu_item == SelectMenuItem::BoardSizeY && game_settings.playing_field_height>5 {
-                    game_settings.playing_field_height -=1;
-                    self.gba_bg_menu_update_req=true;
-
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                } else if self.selected_menu_item == SelectMenuItem::Sound {
-                    game_settings.sound=!game_settings.sound;
-                    self.gba_bg_menu_update_req=true;
-                    
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
+            
+            
+            if gba_input.btn_ctrl.is_just_pressed(Button::B) && !self.parent_menus.is_empty() {
+                self.leave_submenu();
+                audio.play(SoundEvent::MenuBack);
+            }
+            else if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B)) {
+                let item = &self.menu.items[self.selected_item];
+                if item.is_enabled(game_settings) {
+                    match item.kind {
+                        MenuItemKind::Action(action) => {
+                            self.do_action(action, game_settings);
+                            audio.play(if action==MenuAction::Back {SoundEvent::MenuBack} else {SoundEvent::Select});
+                        },
+                        MenuItemKind::Submenu(submenu) => {
+                            self.parent_menus.push((self.menu, self.selected_item));
+                            self.menu = submenu;
+                            self.selected_item = 0;
+                            self.gba_bg_menu_update_req=true;
+                            audio.play(SoundEvent::Select);
+                        },
+                        _ => {},
                     }
+                } else {
+                    audio.play(SoundEvent::Blocked);
                 }
             }
-            else if gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) {
-                if self.selected_menu_item == SelectMenuItem::BoardSizeX && game_settings.playing_field_width<MAX_PLAYING_FIELD_WIDTH {
-                    game_settings.playing_field_width +=1;
-                    self.gba_bg_menu_update_req=true;
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                } else if self.selected_menu_item == SelectMenuItem::BoardSizeY && game_settings.playing_field_height<MAX_PLAYING_FIELD_HEIGHT{
-                    game_settings.playing_field_height +=1;
-                    self.gba_bg_menu_update_req=true;
+        }
+    }
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                } else if self.selected_menu_item == SelectMenuItem::Sound {
-                    game_settings.sound=!game_settings.sound;
-                    self.gba_bg_menu_update_req=true;
-                    
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&CURSOR_MOVE_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                }
+    fn do_action(&mut self, action : MenuAction, game_settings : &game::Settings) {
+        match action {
+            MenuAction::ContinueGame => {
+                self.exit_mode = MenuExitMode::Exit_ContinueGame;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::StartNewGame => {
+                self.exit_mode = MenuExitMode::Exit_StartNewGame;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::StartCampaign => {
+                self.exit_mode = MenuExitMode::Exit_StartCampaign;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::WatchReplay => {
+                self.exit_mode = MenuExitMode::Exit_WatchReplay;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::WatchAiSolve => {
+                self.exit_mode = MenuExitMode::Exit_WatchAiSolve;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::StartTimeAttack => {
+                self.exit_mode = MenuExitMode::Exit_StartTimeAttack;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::StartZenEndless => {
+                self.exit_mode = MenuExitMode::Exit_StartZenEndless;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::HighScores(mode) => {
+                let table = if mode==SessionMode::ZenEndless {HIGH_SCORE_TABLES_COUNT - 1} else {game_settings.time_attack_minutes_index as usize};
+                self.show_high_scores(table, None);
+            },
+            MenuAction::StartTutorial => {
+                self.exit_mode = MenuExitMode::Exit_StartTutorial;
+                self.gba_bg_bg_update_req=true;
+                self.gba_bg_menu_update_req=true;
+            },
+            MenuAction::HowToPlay => {
+                self.help_shown=true;
+                self.show_help_page(self.help_page_nr);
+            },
+            MenuAction::Back => {
+                self.leave_submenu();
+            },
+        }
+    }
+
+    fn show_help_page(&mut self, page_nr : i32) {
+        self.help_page_nr = page_nr;
+        self.help_boards = HELP_PAGES[page_nr as usize].boards.iter().map(|board| {
+            let mut pf = PlayingField::new(board.width, board.height, None);
+            pf.init_with_layout(board.field, board.field, board.towers); //only shown, never played
+            for (x, y, count) in board.sinks.iter() {
+                pf.set_sink((*x as i32, *y as i32), *count);
             }
-            
-            
-            if gba_input.btn_ctrl.is_just_pressed(Button::A.union(Button::B)) {
-                if self.selected_menu_item == SelectMenuItem::StartNewGame {
-                    self.exit_mode = MenuExitMode::Exit_StartNewGame;
-                    self.gba_bg_bg_update_req=true;
-                    self.gba_bg_menu_update_req=true;
+            pf
+        }).collect();
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                }
-                else if self.selected_menu_item == SelectMenuItem::ContinueGame && game_settings.playing_field_data.is_some() {
-                    self.exit_mode = MenuExitMode::Exit_ContinueGame;
-                    self.gba_bg_bg_update_req=true;
-                    self.gba_bg_menu_update_req=true;
+        self.gba_bg_tiles_and_nums_update_req=true;
+        self.gba_bg_menu_update_req=true;
+        self.gba_objs_update_req=true;
+    }
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                }
-                else if self.selected_menu_item == SelectMenuItem::HowToPlay {
-                    self.help_shown=true;
-                    self.gba_bg_menu_update_req=true;
-                    self.gba_objs_update_req=true;
+    //table => index into the high score tables, new_rank => entry to mark
+    pub fn show_high_scores(&mut self, table : usize, new_rank : Option<usize>) {
+        self.high_scores_shown = Some(table);
+        self.high_scores_new_rank = new_rank;
+        self.gba_bg_menu_update_req=true;
+    }
 
-                    if game_settings.sound {
-                        let mut sc = SoundChannel::new(&SELECT_SOUND);
-                        sc.stereo();
-                        gba_mixer.play_sound(sc);
-                    }
-                }
-            }
+    //opens the campaign menu, e.g. after a level
+    pub fn show_campaign(&mut self) {
+        self.parent_menus.clear();
+        self.menu = &MAIN_MENU;
+        self.selected_item = MAIN_MENU.items.iter().position(|item| matches!(item.kind, MenuItemKind::Submenu(m) if core::ptr::eq(m, &CAMPAIGN_MENU))).unwrap_or(0);
+        self.parent_menus.push((self.menu, self.selected_item));
+        self.menu = &CAMPAIGN_MENU;
+        self.selected_item = 0;
+        self.gba_bg_menu_update_req=true;
+    }
 
+    fn leave_submenu(&mut self) {
+        if let Some((menu, selected_item)) = self.parent_menus.pop() {
+            self.menu = menu;
+            self.selected_item = selected_item;
+            self.gba_bg_menu_update_req=true;
         }
     }
 
 
 
-}
+}
\ No newline at end of file
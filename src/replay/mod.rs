@@ -0,0 +1,106 @@
+extern crate alloc;
+
+use alloc::format;
+
+use agb::input::Button;
+
+use agb::display::object::OamManaged;
+use agb::display::tiled::{Tiled0, VRamManager};
+
+use crate::audio::{AudioService, SoundEvent};
+use crate::game;
+use crate::game::logic::PlayingField;
+use crate::game::view::PlayingFieldView;
+use crate::game::Replay;
+use crate::util::gbaex::ButtonControllerAutoRepeat;
+
+
+const FAST_FORWARD_SPEED : u32 = 8; //frames passing per frame while R is held
+
+
+//plays back the recorded moves of a board on a board generated again from the replay
+pub struct ReplayView<'gba> {
+    pf : PlayingField,
+    pfv : PlayingFieldView<'gba>,
+
+    replay : Replay,
+    next_move : usize, //index into the moves of the replay
+    frame : u32, //playback position, compared to the frames of the moves
+    paused : bool,
+    fast_forward : bool,
+
+    finished : bool,
+}
+
+impl<'gba> ReplayView<'gba> {
+
+    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, game_settings : &game::Settings, replay : Replay) -> Self {
+
+        let mut rv = ReplayView {
+            pf : PlayingField::new(replay.width, replay.height, None),
+            pfv : PlayingFieldView::new(gba_tiled0, gba_vram, gba_oam, game_settings.tower_palette),
+
+            replay,
+            next_move : 0,
+            frame : 0,
+            paused : false,
+            fast_forward : false,
+
+            finished : false,
+        };
+
+        rv.pfv.set_dead_end_warnings(game_settings.dead_end_warnings);
+        rv.replay.init_board(&mut rv.pf, &mut rv.pfv);
+        rv
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn play_next_move(&mut self, audio : &mut AudioService) {
+        let mv = &self.replay.moves[self.next_move];
+        self.pfv.replay_move(&mut self.pf, mv, audio);
+        self.frame = self.frame.max(mv.frame);
+        self.next_move += 1;
+    }
+
+    //A plays/pauses, RIGHT steps one move while paused, holding R fast-forwards, B or START quit
+    pub fn handle_input(&mut self, gba_input : &ButtonControllerAutoRepeat, audio : &mut AudioService) {
+
+        if gba_input.btn_ctrl.is_just_pressed(Button::B.union(Button::START)) {
+            self.finished = true;
+            audio.play(SoundEvent::MenuBack);
+            return;
+        }
+
+        if gba_input.btn_ctrl.is_just_pressed(Button::A) {
+            self.paused = !self.paused;
+            audio.play(SoundEvent::Select);
+        }
+        self.fast_forward = gba_input.btn_ctrl.is_pressed(Button::R);
+
+        let count_moves = self.replay.moves.len();
+        if self.paused {
+            if gba_input.is_just_pressed_or_auto_repeated(Button::RIGHT) {
+                if self.next_move<count_moves {
+                    self.play_next_move(audio);
+                } else {
+                    audio.play(SoundEvent::Blocked);
+                }
+            }
+        } else {
+            self.frame += if self.fast_forward {FAST_FORWARD_SPEED} else {1};
+            while self.next_move<count_moves && self.replay.moves[self.next_move].frame<=self.frame {
+                self.play_next_move(audio);
+            }
+        }
+
+        let state = if self.next_move>=count_moves && self.replay.truncated {"CUT"} else if self.next_move>=count_moves {"END"} else if self.paused {"PAUSED"} else if self.fast_forward {"FAST"} else {"PLAY"};
+        self.pfv.set_status_text(Some(format!("REPLAY {}/{} {}", self.next_move, count_moves, state)));
+    }
+
+    pub fn update(&mut self, gba_vram : &mut VRamManager, gba_oam : &OamManaged) {
+        self.pfv.update(&self.pf, gba_vram, gba_oam);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,174 @@
+use agb::display::object::{OamManaged, Object};
+use agb::fixnum::Vector2D;
+use agb::input::Button;
+
+
+use agb::display::tiled::{Tiled0, VRamManager};
+
+use crate::assets::*;
+use crate::audio::{AudioService, SoundEvent};
+use crate::game;
+use crate::game::logic::PlayingField;
+use crate::game::view::{calc_centered_board_offset, PlayingFieldView};
+use crate::util::gbaex::ButtonControllerAutoRepeat;
+
+mod tutorial_steps;
+use tutorial_steps::*;
+
+
+
+const ALL_BUTTONS : [Button; 10] = [
+    Button::A, Button::B, Button::SELECT, Button::START, Button::RIGHT,
+    Button::LEFT, Button::UP, Button::DOWN, Button::R, Button::L,
+];
+
+
+//guides through hand made boards step by step, the board itself is handled by a PlayingFieldView
+//which only gets the input allowed by the current step
+pub struct TutorialView<'gba> {
+    pf : PlayingField,
+    pfv : PlayingFieldView<'gba>,
+
+    step_nr : usize,
+    board_nr : Option<usize>,
+
+    gba_obj_anim_counter : usize,
+    gba_obj_anim_frame : usize,
+    gba_obj_target : Object<'gba>,
+    gba_objs_update_req : bool,
+
+    finished : bool,
+}
+
+impl<'gba> TutorialView<'gba> {
+
+    pub fn new(gba_tiled0 : &'gba Tiled0, gba_vram : &mut VRamManager, gba_oam : &'gba OamManaged, game_settings : &game::Settings) -> Self {
+
+        let board = &TUTORIAL_BOARDS[0];
+
+        let mut tv = TutorialView {
+            pf : PlayingField::new(board.width, board.height, None),
+            pfv : PlayingFieldView::new(gba_tiled0, gba_vram, gba_oam, game_settings.tower_palette),
+
+            step_nr : 0,
+            board_nr : None,
+
+            gba_obj_anim_counter : 0,
+            gba_obj_anim_frame : 0,
+            gba_obj_target : gba_oam.object_sprite(TAG_MAP.get("ArrowDown").sprite(0)),
+            gba_objs_update_req : true,
+
+            finished : false,
+        };
+
+        tv.start_step(0);
+        tv
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn start_step(&mut self, step_nr : usize) {
+        self.step_nr = step_nr;
+        let step = &TUTORIAL_STEPS[step_nr];
+
+        if self.board_nr!=Some(step.board) {
+            self.board_nr = Some(step.board);
+            let board = &TUTORIAL_BOARDS[step.board];
+            self.pf = PlayingField::new(board.width, board.height, None);
+            self.pfv.init_with_layout(&mut self.pf, board.field, board.solution, board.towers);
+        }
+
+        self.pfv.set_hud_text(Some(step.text));
+        self.gba_objs_update_req = true;
+    }
+
+    fn is_goal_reached(&self, goal : &TutorialGoal) -> bool {
+        match goal {
+            TutorialGoal::Confirm => false, //handled in handle_input
+            TutorialGoal::CursorAt(xy) => self.pfv.get_hovered_tile_xy()==*xy,
+            TutorialGoal::Mode(mode) => self.pfv.get_input_mode()==*mode,
+            TutorialGoal::Field(field) => self.pf.field[0..self.pf.area]==field[..],
+            TutorialGoal::Solved => self.pf.is_solved(),
+        }
+    }
+
+    fn next_step(&mut self) {
+        if self.step_nr+1<TUTORIAL_STEPS.len() {
+            self.start_step(self.step_nr+1);
+        } else {
+            self.finished = true;
+            self.gba_objs_update_req = true;
+        }
+    }
+
+    pub fn handle_input(&mut self, gba_input : &ButtonControllerAutoRepeat, audio : &mut AudioService) {
+
+        //START always quits the tutorial
+        if gba_input.btn_ctrl.is_just_pressed(Button::START) {
+            self.finished = true;
+            self.gba_objs_update_req = true;
+            audio.play(SoundEvent::MenuBack);
+            return;
+        }
+
+        let step = &TUTORIAL_STEPS[self.step_nr];
+
+        if let TutorialGoal::Confirm = step.goal {
+            if gba_input.btn_ctrl.is_just_pressed(Button::A) {
+                audio.play(SoundEvent::Select);
+                self.next_step();
+            }
+            return;
+        }
+
+        //restricted input, the board only sees the input while no other button is pressed, held or released,
+        //e.g. releasing R would give a hint and holding it would turn the cursor moves into jumps
+        let mut blocked_buttons = ALL_BUTTONS.iter().filter(|b| !step.allowed_buttons.contains(**b));
+        if blocked_buttons.clone().any(|b| gba_input.btn_ctrl.is_pressed(*b) || gba_input.btn_ctrl.is_just_released(*b)) {
+            if blocked_buttons.any(|b| gba_input.is_just_pressed_or_auto_repeated(*b)) {
+                audio.play(SoundEvent::Blocked);
+            }
+            return;
+        }
+
+        self.pfv.handle_input(&mut self.pf, gba_input, audio);
+
+        if self.is_goal_reached(&step.goal) {
+            self.next_step();
+        }
+    }
+
+    fn update_gba_objs(&mut self, gba_oam : &OamManaged) {
+
+        if self.gba_objs_update_req {
+            self.gba_objs_update_req = false;
+
+            let target = if self.finished {None} else {TUTORIAL_STEPS[self.step_nr].target};
+            if let Some(target) = target {
+                //arrow pointing down onto the target tile
+                let off = calc_centered_board_offset(&self.pf);
+                self.gba_obj_target.set_sprite(gba_oam.sprite(TAG_MAP.get("ArrowDown").sprite(self.gba_obj_anim_frame)));
+                self.gba_obj_target.set_position(Vector2D::new(off.x+target.0*8, off.y+(target.1-1)*8)).show();
+            } else {
+                self.gba_obj_target.hide();
+            }
+
+            gba_oam.commit();
+        }
+    }
+
+    pub fn update(&mut self, gba_vram : &mut VRamManager, gba_oam : &OamManaged) {
+
+        self.gba_obj_anim_counter+=1;
+        if self.gba_obj_anim_counter>=10 {
+            self.gba_obj_anim_counter=0;
+            self.gba_obj_anim_frame = (self.gba_obj_anim_frame+1)%2;
+            self.gba_objs_update_req=true;
+        }
+
+        self.pfv.update(&self.pf, gba_vram, gba_oam);
+        self.update_gba_objs(gba_oam);
+    }
+}
\ No newline at end of file
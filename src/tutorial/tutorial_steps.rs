@@ -0,0 +1,184 @@
+use agb::input::Button;
+
+use crate::game::view::PlayingFieldViewInputMode;
+
+
+//hand made tiny board, field holds the tower index of every tile at the start and solution when solved
+//towers holds origin x, y and flattened height of every tower
+pub struct TutorialBoard {
+    pub width : usize,
+    pub height : usize,
+    pub field : &'static [i32],
+    pub solution : &'static [i32],
+    pub towers : &'static [(usize, usize, i32)],
+}
+
+pub enum TutorialGoal {
+    Confirm, //press A
+    CursorAt((i32, i32)),
+    Mode(PlayingFieldViewInputMode),
+    Field(&'static [i32]), //playing field has exactly this layout
+    Solved,
+}
+
+pub struct TutorialStep {
+    pub board : usize, //index into TUTORIAL_BOARDS, the board gets (re)loaded when it changes
+    pub text : &'static [&'static str], //max 30 characters per line and 4 lines
+    pub allowed_buttons : Button, //input of all other buttons is ignored
+    pub target : Option<(i32, i32)>, //highlighted tile
+    pub goal : TutorialGoal,
+}
+
+
+const DPAD : Button = Button::LEFT.union(Button::RIGHT).union(Button::UP).union(Button::DOWN);
+
+
+pub static TUTORIAL_BOARDS : [TutorialBoard; 2] = [
+    //a single tower of height 3 in the middle of a row
+    TutorialBoard {
+        width : 3,
+        height : 1,
+        field : &[-1, 0, -1],
+        solution : &[0, 0, 0],
+        towers : &[(1, 0, 3)],
+    },
+    //the left tower got spread down instead of right and blocks the row of the other tower
+    TutorialBoard {
+        width : 3,
+        height : 2,
+        field : &[
+            0, -1, -1,
+            0, -1, 1,
+        ],
+        solution : &[
+            0, 0, 0,
+            1, 1, 1,
+        ],
+        towers : &[(0, 0, 2), (2, 1, 3)],
+    },
+];
+
+pub static TUTORIAL_STEPS : [TutorialStep; 11] = [
+    TutorialStep {
+        board : 0,
+        text : &[
+            "Welcome to ZenF!",
+            "Flatten the towers until they",
+            "cover the whole board.",
+            "Press A to continue.",
+        ],
+        allowed_buttons : Button::A,
+        target : None,
+        goal : TutorialGoal::Confirm,
+    },
+    TutorialStep {
+        board : 0,
+        text : &[
+            "The number is the height of",
+            "the tower. Move the cursor",
+            "onto it with RIGHT.",
+        ],
+        allowed_buttons : Button::LEFT.union(Button::RIGHT),
+        target : Some((1, 0)),
+        goal : TutorialGoal::CursorAt((1, 0)),
+    },
+    TutorialStep {
+        board : 0,
+        text : &[
+            "Press A to select the tower",
+            "and switch to Flatten mode.",
+        ],
+        allowed_buttons : Button::A,
+        target : Some((1, 0)),
+        goal : TutorialGoal::Mode(PlayingFieldViewInputMode::Flatten),
+    },
+    TutorialStep {
+        board : 0,
+        text : &[
+            "The arrows point outwards in",
+            "Flatten mode. Press LEFT to",
+            "spread the tower one tile.",
+        ],
+        allowed_buttons : Button::LEFT,
+        target : Some((0, 0)),
+        goal : TutorialGoal::Field(&[0, 0, -1]),
+    },
+    TutorialStep {
+        board : 0,
+        text : &[
+            "Its height shrinks by one.",
+            "Now press RIGHT.",
+        ],
+        allowed_buttons : Button::RIGHT,
+        target : Some((2, 0)),
+        goal : TutorialGoal::Field(&[0, 0, 0]),
+    },
+    TutorialStep {
+        board : 0,
+        text : &[
+            "Solved! Every tile is covered",
+            "and the height is 1. Press A",
+            "to leave Flatten mode.",
+        ],
+        allowed_buttons : Button::A,
+        target : None,
+        goal : TutorialGoal::Mode(PlayingFieldViewInputMode::MoveSelect),
+    },
+    TutorialStep {
+        board : 1,
+        text : &[
+            "Oops, the left tower spread",
+            "down and blocks the other one.",
+            "Press B to select it in",
+            "De-Flatten mode.",
+        ],
+        allowed_buttons : Button::B,
+        target : Some((0, 0)),
+        goal : TutorialGoal::Mode(PlayingFieldViewInputMode::Deflatten),
+    },
+    TutorialStep {
+        board : 1,
+        text : &[
+            "The arrows point inwards now.",
+            "Press UP to take the lower",
+            "tile back.",
+        ],
+        allowed_buttons : Button::UP,
+        target : None, //the inward pointing arrows already mark the tile
+        goal : TutorialGoal::Field(&[0, -1, -1, -1, -1, 1]),
+    },
+    TutorialStep {
+        board : 1,
+        text : &[
+            "Press A to switch straight to",
+            "Flatten mode, then spread the",
+            "tower to the right twice.",
+        ],
+        allowed_buttons : Button::A.union(Button::RIGHT),
+        target : Some((2, 0)),
+        goal : TutorialGoal::Field(&[0, 0, 0, -1, -1, 1]),
+    },
+    TutorialStep {
+        board : 1,
+        text : &[
+            "Press A to leave Flatten mode",
+            "and solve the board with the",
+            "other tower on your own.",
+        ],
+        allowed_buttons : DPAD.union(Button::A).union(Button::B),
+        target : Some((2, 1)),
+        goal : TutorialGoal::Solved,
+    },
+    TutorialStep {
+        board : 1,
+        text : &[
+            "Well done!",
+            "You are ready for bigger",
+            "boards. Press A to go back",
+            "to the menu.",
+        ],
+        allowed_buttons : Button::A,
+        target : None,
+        goal : TutorialGoal::Confirm,
+    },
+];
\ No newline at end of file
@@ -1,13 +1,15 @@
 
 use agb::input::{ButtonController, Button};
 
-const AUTO_REPEAT_INITIAL_FRAMES : u8 = 20; //frames which must be elapsed before auto repeating starts
-const AUTO_REPEAT_INTERVAL_FRAMES : u8 = 4; //after initial phase the frames which must elapse between each repeat
+use crate::game::{Action, Controls};
 
 pub struct ButtonControllerAutoRepeat {
     elapsed_frames_while_pressed_per_key : [u8; 10], 
     initial : u32,
     just_auto_repeated : u32,
+    auto_repeat_initial_frames : u8, //frames which must be elapsed before auto repeating starts
+    auto_repeat_interval_frames : u8, //after initial phase the frames which must elapse between each repeat
+    controls : Controls,
     pub btn_ctrl : ButtonController,
 }
 
@@ -17,10 +19,20 @@ impl ButtonControllerAutoRepeat {
             elapsed_frames_while_pressed_per_key : [0; 10],
             initial : 0,
             just_auto_repeated : 0,
+            auto_repeat_initial_frames : 20,
+            auto_repeat_interval_frames : 4,
+            controls : Controls::new(),
             btn_ctrl : ButtonController::new(),
         }
     }
 
+    //repeat timing and action buttons
+    pub fn apply_controls(&mut self, controls : &Controls) {
+        self.auto_repeat_initial_frames = controls.repeat_delay;
+        self.auto_repeat_interval_frames = controls.repeat_interval();
+        self.controls = *controls;
+    }
+
     pub fn update(&mut self) {
         self.btn_ctrl.update();
 
@@ -28,7 +40,7 @@ impl ButtonControllerAutoRepeat {
             if self.btn_ctrl.is_pressed(Button::from_bits_retain(1<<i)) {
                 self.elapsed_frames_while_pressed_per_key[i as usize] += 1;
                 let initial = self.initial&(1<<i)==0;
-                let frames = if initial {AUTO_REPEAT_INITIAL_FRAMES} else {AUTO_REPEAT_INTERVAL_FRAMES};
+                let frames = if initial {self.auto_repeat_initial_frames} else {self.auto_repeat_interval_frames};
                 if self.elapsed_frames_while_pressed_per_key[i as usize] > frames {
                     self.just_auto_repeated |= 1<<i;
                     self.elapsed_frames_while_pressed_per_key[i as usize] = 0;
@@ -51,4 +63,24 @@ impl ButtonControllerAutoRepeat {
     pub fn is_just_pressed_or_auto_repeated(&self, keys : Button) -> bool {
         self.btn_ctrl.is_just_pressed(keys) || self.is_just_auto_repeated(keys)
     }
+
+    pub fn is_action_pressed(&self, action : Action) -> bool {
+        self.btn_ctrl.is_pressed(self.controls.button(action))
+    }
+
+    pub fn is_action_just_released(&self, action : Action) -> bool {
+        self.btn_ctrl.is_just_released(self.controls.button(action))
+    }
+
+    pub fn is_action_just_pressed(&self, action : Action) -> bool {
+        self.btn_ctrl.is_just_pressed(self.controls.button(action))
+    }
+
+    pub fn is_action_just_pressed_or_auto_repeated(&self, action : Action) -> bool {
+        self.is_just_pressed_or_auto_repeated(self.controls.button(action))
+    }
+
+    pub fn button_name(&self, action : Action) -> &'static str {
+        self.controls.button_name(action)
+    }
 }
@@ -1,2 +1,3 @@
 pub mod gbaex;
-pub mod rng;
\ No newline at end of file
+pub mod rng;
+pub mod text;
\ No newline at end of file
@@ -0,0 +1,94 @@
+use agb::display::tile_data::TileData;
+use agb::display::tiled::{RegularMap, VRamManager};
+
+const FONT_FIRST_CHAR : u8 = b' ';
+const FONT_LAST_CHAR : u8 = b'~';
+const FONT_CHARS_PER_STYLE : usize = 96; //font.png holds ascii 32..126 (16 per row) once per style
+const FONT_FALLBACK_CHAR : u8 = b'?';
+
+const MAX_NUMBER_DIGITS : usize = 11; //enough for i32::MIN
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TextStyle {
+    Normal,
+    Disabled,
+}
+
+//prints text with the 8x8 glyph atlas font.png, one character per tile
+pub struct TextRenderer {
+    font : &'static TileData,
+    style : TextStyle,
+}
+
+impl TextRenderer {
+
+    pub fn new(font : &'static TileData) -> Self {
+        TextRenderer {
+            font,
+            style : TextStyle::Normal,
+        }
+    }
+
+    //style used by all following print calls
+    pub fn set_style(&mut self, style : TextStyle) {
+        self.style = style;
+    }
+
+    fn glyph_index(c : u8, style : TextStyle) -> usize {
+        let c = if (FONT_FIRST_CHAR..=FONT_LAST_CHAR).contains(&c) {c} else {FONT_FALLBACK_CHAR};
+        (c - FONT_FIRST_CHAR) as usize + (if style==TextStyle::Disabled {FONT_CHARS_PER_STYLE} else {0})
+    }
+
+    //prints text into the field of the given width starting at pos, aligned inside the field
+    //characters falling outside of the field are clipped, returns the count of printed characters
+    pub fn print_str(&self, map : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16), width : u16, text : &str, align : TextAlign) -> u16 {
+        self.print_bytes(map, gba_vram, pos, width, text.as_bytes(), align)
+    }
+
+    pub fn print_number(&self, map : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16), width : u16, number : i32, align : TextAlign) -> u16 {
+        let mut digits = [0u8; MAX_NUMBER_DIGITS];
+        let mut di = MAX_NUMBER_DIGITS;
+        let mut n = number.unsigned_abs();
+        loop {
+            di-=1;
+            digits[di] = b'0' + (n%10) as u8;
+            n/=10;
+            if n==0 {
+                break;
+            }
+        }
+        if number<0 {
+            di-=1;
+            digits[di] = b'-';
+        }
+        self.print_bytes(map, gba_vram, pos, width, &digits[di..], align)
+    }
+
+    fn print_bytes(&self, map : &mut RegularMap, gba_vram : &mut VRamManager, pos : (u16, u16), width : u16, text : &[u8], align : TextAlign) -> u16 {
+        let len = text.len() as i32;
+        let start = match align {
+            TextAlign::Left => 0,
+            TextAlign::Center => (width as i32 - len)/2,
+            TextAlign::Right => width as i32 - len,
+        };
+
+        let mut count = 0;
+        for (i, c) in text.iter().enumerate() {
+            let x = start + i as i32;
+            if x<0 || x>=width as i32 {
+                continue;
+            }
+            let tile_setting = self.font.tile_settings[Self::glyph_index(*c, self.style)];
+            map.set_tile(gba_vram, (pos.0 + x as u16, pos.1), &self.font.tiles, tile_setting);
+            count+=1;
+        }
+        count
+    }
+}
\ No newline at end of file